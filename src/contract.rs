@@ -1,20 +1,33 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cosmwasm_std::{
+    to_json_binary, Addr, Api, Attribute, Binary, Deps, DepsMut, Env, HexBinary, MessageInfo,
+    Response, StdResult, Uint128,
+};
+use cw2::{get_contract_version, set_contract_version};
+use sha3::{Digest as _, Keccak256};
+use std::str::FromStr;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, PalomaMsg, QueryMsg};
+use crate::state::{
+    ChainSettings, PauseScope, PauseStatus, PurchaseRecord, Purchaser, SaleCaps, State,
+    TimelockedChange, TokenMetadata, CHAIN_SETTINGS, DISTRIBUTION, ERC20_ADDRESSES, PAUSE_STATUS,
+    PENDING_OWNER, PROPOSALS, PURCHASE_RECORDS, ROUNDS, SALE_CAPS, STATE, TOKEN_METADATA,
+    TOTAL_ALLOCATION, TOTAL_PURCHASED,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:gpu-dao-cw";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const MAX_SUBDENOM_LENGTH: usize = 44;
+const MAX_DECIMALS: u32 = 18;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
@@ -22,16 +35,35 @@ pub fn instantiate(
         owners: msg
             .owners
             .iter()
-            .map(|x| deps.api.addr_validate(x).unwrap())
-            .collect(),
+            .map(|x| deps.api.addr_validate(x))
+            .collect::<StdResult<_>>()?,
         finished: false,
+        cancelled: false,
+        threshold: msg.threshold.unwrap_or(1),
+        timelock_delay: msg.timelock_delay,
+        start_time: msg.start_time,
+        end_time: msg.end_time,
     };
 
-    if !state.owners.contains(&deps.api.addr_validate(&info.sender.to_string()).unwrap()) {
-        state.owners.push(deps.api.addr_validate(&info.sender.to_string()).unwrap());
+    if !state.owners.contains(&info.sender) {
+        state.owners.push(info.sender);
+    }
+    ensure_valid_threshold(&state)?;
+    if state.start_time >= state.end_time || state.end_time <= env.block.time {
+        return Err(ContractError::InvalidSaleWindow {
+            reason: "end_time must be after start_time and in the future".to_string(),
+        });
     }
-    
+
+    validate_token_metadata(&msg.token_metadata)?;
+    let sale_caps = msg.sale_caps.unwrap_or_default();
+    validate_sale_caps(&sale_caps)?;
+
     STATE.save(deps.storage, &state)?;
+    TOKEN_METADATA.save(deps.storage, &msg.token_metadata)?;
+    SALE_CAPS.save(deps.storage, &sale_caps)?;
+    TOTAL_PURCHASED.save(deps.storage, &Uint128::zero())?;
+    TOTAL_ALLOCATION.save(deps.storage, &Uint128::zero())?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::new().add_attribute("action", "instantiate"))
 }
@@ -39,82 +71,965 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<PalomaMsg>, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if state.threshold > 1 && msg.requires_proposal() {
+        return Err(ContractError::ProposalRequired {});
+    }
+    dispatch(deps, env, info, msg)
+}
+
+/// Routes a message to its handler; also used to run approved proposals.
+fn dispatch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<PalomaMsg>, ContractError> {
+    ensure_not_paused(deps.as_ref(), &msg)?;
     match msg {
-        ExecuteMsg::Purchase{purchaser,amount}=>execute::purchase(deps,info,purchaser,amount),
-        ExecuteMsg::Finalize{mint_amount,distribute_amount,pusd_amount,}=>execute::finalize(deps,info,mint_amount,distribute_amount,pusd_amount),
-        ExecuteMsg::Refund{}=>execute::refund(deps),
-        ExecuteMsg::SetPaloma { chain_id } => execute::set_paloma(deps, chain_id),
-        ExecuteMsg::UpdateCompass { chain_id, new_compass } => 
-            execute::update_compass(deps, chain_id, new_compass),
-        ExecuteMsg::UpdateRefundWallet { chain_id, new_refund_wallet } => 
-            execute::update_refund_wallet(deps, chain_id, new_refund_wallet),
-        ExecuteMsg::UpdateGasFee { chain_id, new_gas_fee } => 
-            execute::update_gas_fee(deps, chain_id, new_gas_fee),
-        ExecuteMsg::UpdateServiceFeeCollector { chain_id, new_service_fee_collector } => 
-            execute::update_service_fee_collector(deps, chain_id, new_service_fee_collector),
-        ExecuteMsg::UpdateServiceFee { chain_id, new_service_fee } => 
-            execute::update_service_fee(deps, chain_id, new_service_fee),
+        ExecuteMsg::Purchase {
+            chain_id,
+            round_id,
+            purchaser,
+            amount,
+            allocation_proof,
+            origin,
+        } => execute::purchase(
+            deps,
+            env,
+            info,
+            chain_id,
+            round_id,
+            purchaser,
+            amount,
+            allocation_proof,
+            origin,
+        ),
+        ExecuteMsg::BatchPurchase { entries } => execute::batch_purchase(deps, env, info, entries),
+        ExecuteMsg::IngestPurchaseLogs { chain_id, logs } => {
+            execute::ingest_purchase_logs(deps, env, info, chain_id, logs)
+        }
+        ExecuteMsg::AdjustPurchase {
+            chain_id,
+            round_id,
+            purchaser,
+            delta,
+            reason,
+        } => execute::adjust_purchase(
+            deps, env, info, chain_id, round_id, purchaser, delta, reason,
+        ),
+        ExecuteMsg::Finalize {
+            mint_amount,
+            distribute_amount,
+            pusd_amount,
+            treasury,
+        } => execute::finalize(
+            deps,
+            env,
+            info,
+            mint_amount,
+            distribute_amount,
+            pusd_amount,
+            treasury,
+        ),
+        ExecuteMsg::AddOwner { owner } => execute::add_owner(deps, info, owner),
+        ExecuteMsg::RemoveOwner { owner } => execute::remove_owner(deps, info, owner),
+        ExecuteMsg::ProposeOwner {
+            new_owner,
+            replacing,
+        } => execute::propose_owner(deps, info, new_owner, replacing),
+        ExecuteMsg::AcceptOwnership {} => execute::accept_ownership(deps, info),
+        ExecuteMsg::UpdateThreshold { threshold } => {
+            execute::update_threshold(deps, info, threshold)
+        }
+        ExecuteMsg::Propose { msg, expires_in } => {
+            execute::propose(deps, env, info, *msg, expires_in)
+        }
+        ExecuteMsg::Vote { proposal_id } => execute::vote(deps, env, info, proposal_id),
+        ExecuteMsg::ExecuteProposal { proposal_id } => {
+            execute::execute_proposal(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::CancelProposal { proposal_id } => {
+            execute::cancel_proposal(deps, info, proposal_id)
+        }
+        ExecuteMsg::CreateRound {
+            name,
+            price,
+            cap,
+            start_time,
+            end_time,
+            allowlist_only,
+        } => execute::create_round(
+            deps,
+            info,
+            name,
+            price,
+            cap,
+            start_time,
+            end_time,
+            allowlist_only,
+        ),
+        ExecuteMsg::OpenRound { round_id } => execute::set_round_open(deps, info, round_id, true),
+        ExecuteMsg::CloseRound { round_id } => execute::set_round_open(deps, info, round_id, false),
+        ExecuteMsg::UpdateRoundMerkleRoot {
+            round_id,
+            merkle_root,
+        } => execute::update_round_merkle_root(deps, info, round_id, merkle_root),
+        ExecuteMsg::UpdateRoundAllowlist {
+            round_id,
+            add,
+            remove,
+        } => execute::update_round_allowlist(deps, info, round_id, add, remove),
+        ExecuteMsg::ExtendSaleWindow { end_time } => {
+            execute::extend_sale_window(deps, env, info, end_time)
+        }
+        ExecuteMsg::UpdateSaleCaps { sale_caps } => {
+            execute::update_sale_caps(deps, info, sale_caps)
+        }
+        ExecuteMsg::UpdateTokenMetadata { token_metadata } => {
+            execute::update_token_metadata(deps, info, token_metadata)
+        }
+        ExecuteMsg::ProcessDistribution { limit } => execute::process_distribution(deps, limit),
+        ExecuteMsg::BridgeToken {
+            chain_id,
+            erc20_address,
+        } => execute::bridge_token(deps, info, chain_id, erc20_address),
+        ExecuteMsg::CancelSale {} => execute::cancel_sale(deps, info),
+        ExecuteMsg::Refund { purchasers } => execute::refund(deps, info, purchasers),
+        ExecuteMsg::SetPaloma { chain_id } => execute::set_paloma(deps, info, chain_id),
+        ExecuteMsg::UpdateCompass {
+            chain_id,
+            new_compass,
+        } => execute::queue_change(
+            deps,
+            env,
+            info,
+            Some(chain_id),
+            TimelockedChange::Compass { new_compass },
+        ),
+        ExecuteMsg::UpdateRefundWallet {
+            chain_id,
+            new_refund_wallet,
+        } => execute::queue_change(
+            deps,
+            env,
+            info,
+            Some(chain_id),
+            TimelockedChange::RefundWallet { new_refund_wallet },
+        ),
+        ExecuteMsg::UpdateGasFee {
+            chain_id,
+            new_gas_fee,
+        } => execute::queue_change(
+            deps,
+            env,
+            info,
+            Some(chain_id),
+            TimelockedChange::GasFee { new_gas_fee },
+        ),
+        ExecuteMsg::UpdateServiceFeeCollector {
+            chain_id,
+            new_service_fee_collector,
+        } => execute::queue_change(
+            deps,
+            env,
+            info,
+            Some(chain_id),
+            TimelockedChange::ServiceFeeCollector {
+                new_service_fee_collector,
+            },
+        ),
+        ExecuteMsg::UpdateServiceFee {
+            chain_id,
+            new_service_fee,
+        } => execute::queue_change(
+            deps,
+            env,
+            info,
+            Some(chain_id),
+            TimelockedChange::ServiceFee { new_service_fee },
+        ),
+        ExecuteMsg::ExecuteQueuedChange { change_id } => {
+            execute::execute_queued_change(deps, env, info, change_id)
+        }
+        ExecuteMsg::CancelQueuedChange { change_id } => {
+            execute::cancel_queued_change(deps, info, change_id)
+        }
+        ExecuteMsg::UpdateTimelockDelay { delay } => execute::queue_change(
+            deps,
+            env,
+            info,
+            None,
+            TimelockedChange::TimelockDelay { new_delay: delay },
+        ),
+        ExecuteMsg::RegisterChain {
+            chain_id,
+            chain_settings,
+        } => execute::register_chain(deps, info, chain_id, chain_settings),
+        ExecuteMsg::UpdateChain {
+            chain_id,
+            chain_settings,
+        } => execute::queue_change(
+            deps,
+            env,
+            info,
+            Some(chain_id),
+            TimelockedChange::ChainSettings { chain_settings },
+        ),
+        ExecuteMsg::RemoveChain { chain_id } => execute::remove_chain(deps, info, chain_id),
+        ExecuteMsg::CreateJob {
+            chain_id,
+            job_id,
+            definition,
+            payload,
+            payload_modifiable,
+            is_mev,
+            permissions,
+        } => execute::queue_change(
+            deps,
+            env,
+            info,
+            Some(chain_id),
+            TimelockedChange::CreateJob {
+                job_id,
+                definition,
+                payload,
+                payload_modifiable,
+                is_mev,
+                permissions,
+            },
+        ),
+        ExecuteMsg::UpdateJob {
+            chain_id,
+            definition,
+            payload,
+            permissions,
+        } => execute::queue_change(
+            deps,
+            env,
+            info,
+            Some(chain_id),
+            TimelockedChange::UpdateJob {
+                definition,
+                payload,
+                permissions,
+            },
+        ),
+        ExecuteMsg::Pause { scopes } => execute::pause(deps, info, scopes),
+        ExecuteMsg::Unpause { scopes } => execute::unpause(deps, info, scopes),
+    }
+}
+
+fn ensure_not_paused(deps: Deps, msg: &ExecuteMsg) -> Result<(), ContractError> {
+    if !msg.is_pausable() {
+        return Ok(());
+    }
+    let status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    if status.all {
+        return Err(ContractError::Paused {
+            scope: "all".to_string(),
+        });
+    }
+    match msg.pause_scope() {
+        Some(scope) if status.scopes.contains(&scope) => Err(ContractError::Paused {
+            scope: scope.as_str().to_string(),
+        }),
+        _ => Ok(()),
     }
 }
 
+fn ensure_owner(state: &State, sender: &Addr) -> Result<(), ContractError> {
+    if !state.owners.contains(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+fn ensure_valid_threshold(state: &State) -> Result<(), ContractError> {
+    if state.threshold == 0 || state.threshold as usize > state.owners.len() {
+        return Err(ContractError::InvalidThreshold {});
+    }
+    Ok(())
+}
+
+fn ensure_not_finalized(state: &State) -> Result<(), ContractError> {
+    if state.finished {
+        return Err(ContractError::AlreadyFinalized {});
+    }
+    Ok(())
+}
+
+fn ensure_not_cancelled(state: &State) -> Result<(), ContractError> {
+    if state.cancelled {
+        return Err(ContractError::SaleCancelled {});
+    }
+    Ok(())
+}
+
+fn validate_token_metadata(token: &TokenMetadata) -> Result<(), ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidTokenMetadata {
+        reason: reason.to_string(),
+    };
+    if token.subdenom.is_empty() || token.subdenom.len() > MAX_SUBDENOM_LENGTH {
+        return Err(invalid("subdenom must be 1 to 44 characters"));
+    }
+    if !token
+        .subdenom
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        return Err(invalid("subdenom may only contain [a-zA-Z0-9._-]"));
+    }
+    if token.name.is_empty() || token.symbol.is_empty() || token.display.is_empty() {
+        return Err(invalid("name, symbol and display must not be empty"));
+    }
+    if token.decimals == 0 || token.decimals > MAX_DECIMALS {
+        return Err(invalid("decimals must be between 1 and 18"));
+    }
+    Ok(())
+}
+
+fn validate_sale_caps(caps: &SaleCaps) -> Result<(), ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidSaleCaps {
+        reason: reason.to_string(),
+    };
+    if let (Some(soft_cap), Some(hard_cap)) = (caps.soft_cap, caps.hard_cap) {
+        if soft_cap > hard_cap {
+            return Err(invalid("soft cap exceeds hard cap"));
+        }
+    }
+    if let (Some(min_purchase), Some(max_per_purchaser)) =
+        (caps.min_purchase, caps.max_per_purchaser)
+    {
+        if min_purchase > max_per_purchaser {
+            return Err(invalid("minimum purchase exceeds per-purchaser cap"));
+        }
+    }
+    Ok(())
+}
+
+fn ensure_sale_open(state: &State, env: &Env) -> Result<(), ContractError> {
+    if env.block.time < state.start_time {
+        return Err(ContractError::SaleNotStarted {
+            start_time: state.start_time,
+        });
+    }
+    if env.block.time >= state.end_time {
+        return Err(ContractError::SaleEnded {
+            end_time: state.end_time,
+        });
+    }
+    Ok(())
+}
+
+fn validate_chain_settings(chain_settings: &ChainSettings) -> Result<(), ContractError> {
+    evm_address(&chain_settings.compass)?;
+    evm_address(&chain_settings.refund_wallet)?;
+    evm_address(&chain_settings.service_fee_collector)?;
+    if let Some(sale_contract) = &chain_settings.sale_contract {
+        evm_address(sale_contract)?;
+    }
+    Ok(())
+}
+
+fn evm_address(address: &str) -> Result<ethabi::Address, ContractError> {
+    ethabi::Address::from_str(address).map_err(|_| ContractError::InvalidEvmAddress {
+        address: address.to_string(),
+    })
+}
+
+/// Parses a purchaser into its canonical form: `0x` addresses are EIP-55
+/// checksummed, anything else must be a valid bech32 address.
+fn parse_purchaser(api: &dyn Api, purchaser: &str) -> Result<Purchaser, ContractError> {
+    let invalid = || ContractError::InvalidPurchaser {
+        purchaser: purchaser.to_string(),
+    };
+    let Some(hex) = purchaser.strip_prefix("0x") else {
+        let addr = api
+            .addr_validate(&purchaser.to_lowercase())
+            .map_err(|_| invalid())?;
+        return Ok(Purchaser::Cosmos(addr));
+    };
+    if hex.len() != 40 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let checksummed = eip55_checksum(hex);
+    // Single-case input carries no checksum; mixed case must match it.
+    let mixed_case =
+        hex.bytes().any(|b| b.is_ascii_lowercase()) && hex.bytes().any(|b| b.is_ascii_uppercase());
+    if mixed_case && checksummed[2..] != *hex {
+        return Err(invalid());
+    }
+    Ok(Purchaser::Evm(checksummed))
+}
+
+fn eip55_checksum(hex: &str) -> String {
+    let lower = hex.to_ascii_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+fn purchase_by_origin(
+    deps: Deps,
+    chain_id: &str,
+    tx_hash: &HexBinary,
+    log_index: u64,
+) -> StdResult<Option<PurchaseRecord>> {
+    Ok(PURCHASE_RECORDS
+        .idx
+        .origin
+        .item(
+            deps.storage,
+            (chain_id.to_string(), tx_hash.to_vec(), log_index),
+        )?
+        .map(|(_, record)| record))
+}
+
 pub mod execute {
     use std::collections::BTreeMap;
 
-    use cosmwasm_std::{CosmosMsg, Uint128, Uint256};
-    use ethabi::{Address, Contract, Function, Param, ParamType, StateMutability, Token, Uint};
+    use cosmwasm_std::{
+        Attribute, BankMsg, Coin, CosmosMsg, Decimal, Event, HexBinary, Int128, Order, StdError,
+        Timestamp, Uint256,
+    };
+    use cw_storage_plus::Bound;
+    use ethabi::{
+        Address, Contract, EventParam, Function, Hash, Param, ParamType, RawLog, StateMutability,
+        Token, Uint,
+    };
+    use sha2::{Digest, Sha256};
 
-    use crate::{msg::{CreateDenomMsg, MintMsg, PalomaMsg}, state::PURCHASE_LIST};
-    use std::str::FromStr;
     use super::*;
+    use crate::msg::{
+        AllocationProof, CreateDenomMsg, CreateJob, DenomUnit, ExecuteJob, JobDefinition,
+        JobPermissions, Metadata, MintMsg, PalomaMsg, PurchaseEntry, PurchaseOrigin, RelayedLog,
+        SendTx, SetErc20ToDenom, UpdateJob,
+    };
+    use crate::state::{
+        Distribution, PendingOwner, Proposal, ProposalStatus, PurchaseAdjustment, QueuedChange,
+        Round, ADJUSTMENTS, ADJUSTMENT_COUNT, ALLOCATIONS, CHAIN_PURCHASED, DISTRIBUTION,
+        ERC20_ADDRESSES, PENDING_OWNER, PROPOSAL_COUNT, PURCHASE_CHAINS, PURCHASE_LIST,
+        PURCHASE_RECORD_COUNT, QUEUED_CHANGES, QUEUED_CHANGE_COUNT, REFUNDED, ROUND_ALLOWLIST,
+        ROUND_COUNT, ROUND_PURCHASES,
+    };
 
+    const DEFAULT_PROPOSAL_DURATION: u64 = 7 * 24 * 60 * 60;
+    const DEFAULT_DISTRIBUTION_LIMIT: u32 = 30;
+    const MAX_DISTRIBUTION_LIMIT: u32 = 100;
+
+    #[allow(clippy::too_many_arguments)]
     pub fn purchase(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
+        chain_id: String,
+        round_id: Option<u64>,
         purchaser: String,
         amount: Uint128,
-    ) -> Result<Response, ContractError> {
+        allocation_proof: Option<AllocationProof>,
+        origin: PurchaseOrigin,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
         let state = STATE.load(deps.storage)?;
-        assert!(
-            state.owners.iter().any(|x| x == info.sender),
-            "Unauthorized"
-        );
-        assert!(
-            state.finished == false,
-            "The contract has already been finalized"
-        );
+        ensure_owner(&state, &info.sender)?;
+        let record = apply_purchase(
+            deps,
+            &env,
+            &state,
+            chain_id,
+            round_id,
+            purchaser,
+            amount,
+            allocation_proof,
+            origin,
+        )?;
+        Ok(Response::new()
+            .add_attribute("action", "purchase")
+            .add_attributes(purchase_attributes(&record)))
+    }
+
+    pub fn batch_purchase(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        entries: Vec<PurchaseEntry>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        if entries.is_empty() {
+            return Err(ContractError::EmptyBatch {});
+        }
+
+        let mut response = Response::new().add_attributes(vec![
+            ("action", "batch_purchase"),
+            ("entries", entries.len().to_string().as_str()),
+        ]);
+        for (index, entry) in entries.into_iter().enumerate() {
+            let record = apply_purchase(
+                deps.branch(),
+                &env,
+                &state,
+                entry.chain_id,
+                entry.round_id,
+                entry.purchaser,
+                entry.amount,
+                entry.allocation_proof,
+                entry.origin,
+            )?;
+            response = response.add_event(
+                Event::new("purchase")
+                    .add_attribute("entry", index.to_string())
+                    .add_attributes(purchase_attributes(&record)),
+            );
+        }
+        Ok(response)
+    }
+
+    pub fn ingest_purchase_logs(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        chain_id: String,
+        logs: Vec<RelayedLog>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
+        let sale_contract = match &chain_settings.sale_contract {
+            Some(sale_contract) => evm_address(sale_contract)?,
+            None => return Err(ContractError::SaleContractNotSet { chain_id }),
+        };
 
-        PURCHASE_LIST.update(deps.storage, purchaser, |old| -> StdResult<_> {
+        let event = ethabi::Event {
+            name: "Purchased".to_string(),
+            inputs: vec![
+                EventParam {
+                    name: "purchaser".to_string(),
+                    kind: ParamType::Address,
+                    indexed: true,
+                },
+                EventParam {
+                    name: "token".to_string(),
+                    kind: ParamType::Address,
+                    indexed: true,
+                },
+                EventParam {
+                    name: "amount".to_string(),
+                    kind: ParamType::Uint(256),
+                    indexed: false,
+                },
+            ],
+            anonymous: false,
+        };
+
+        let mut response = Response::new().add_attributes(vec![
+            ("action", "ingest_purchase_logs"),
+            ("chain_id", chain_id.as_str()),
+            ("logs", logs.len().to_string().as_str()),
+        ]);
+        for log in logs {
+            if evm_address(&log.address)? != sale_contract {
+                return Err(ContractError::UnexpectedLogEmitter {
+                    chain_id,
+                    address: log.address,
+                });
+            }
+            let topics = log
+                .topics
+                .iter()
+                .map(|topic| {
+                    <[u8; 32]>::try_from(topic.as_slice())
+                        .map(Hash::from)
+                        .map_err(|_| ContractError::InvalidPurchaseLog {
+                            reason: "topics must be 32 bytes".to_string(),
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut values = event
+                .parse_log(RawLog {
+                    topics,
+                    data: log.data.to_vec(),
+                })?
+                .params
+                .into_iter()
+                .map(|param| param.value);
+            let (Some(purchaser), Some(token), Some(amount)) = (
+                values.next().and_then(Token::into_address),
+                values.next().and_then(Token::into_address),
+                values.next().and_then(Token::into_uint),
+            ) else {
+                return Err(ContractError::InvalidPurchaseLog {
+                    reason: "unexpected Purchased parameters".to_string(),
+                });
+            };
+            let amount = u128::try_from(amount).map_err(|_| ContractError::InvalidPurchaseLog {
+                reason: "amount exceeds 128 bits".to_string(),
+            })?;
+
+            let record = apply_purchase(
+                deps.branch(),
+                &env,
+                &state,
+                chain_id.clone(),
+                None,
+                format!("{:#x}", purchaser),
+                Uint128::new(amount),
+                None,
+                PurchaseOrigin {
+                    tx_hash: log.tx_hash,
+                    log_index: log.log_index,
+                    payment_token: format!("{:#x}", token),
+                },
+            )?;
+            response = response
+                .add_event(Event::new("purchase").add_attributes(purchase_attributes(&record)));
+        }
+        Ok(response)
+    }
+
+    /// Checks and credits a purchase, returning the stored record.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_purchase(
+        deps: DepsMut,
+        env: &Env,
+        state: &State,
+        chain_id: String,
+        round_id: Option<u64>,
+        purchaser: String,
+        amount: Uint128,
+        allocation_proof: Option<AllocationProof>,
+        origin: PurchaseOrigin,
+    ) -> Result<PurchaseRecord, ContractError> {
+        ensure_not_finalized(state)?;
+        ensure_not_cancelled(state)?;
+        if amount.is_zero() {
+            return Err(ContractError::ZeroAmount {});
+        }
+        load_chain_settings(deps.as_ref(), &chain_id)?;
+        let purchaser = parse_purchaser(deps.api, &purchaser)?;
+        validate_purchase_origin(&origin)?;
+        ensure_not_duplicate(deps.as_ref(), &chain_id, &origin)?;
+        ensure_within_caps(deps.as_ref(), &purchaser, amount)?;
+
+        ensure_sale_open(state, env)?;
+
+        // Round purchases also follow the round's window and price; the rest are
+        // allocated one token per payment unit.
+        let allocation = match round_id {
+            Some(round_id) => {
+                let mut round = load_round(deps.as_ref(), round_id)?;
+                ensure_round_open(
+                    deps.as_ref(),
+                    env,
+                    &round,
+                    &purchaser,
+                    amount,
+                    allocation_proof.as_ref(),
+                )?;
+                round.total_purchased += amount;
+                ROUNDS.save(deps.storage, round_id, &round)?;
+                ROUND_PURCHASES.update(
+                    deps.storage,
+                    (round_id, purchaser.clone()),
+                    |old| -> StdResult<_> { Ok(old.unwrap_or_default() + amount) },
+                )?;
+                amount
+                    .checked_div_floor(round.price)
+                    .map_err(|e| StdError::generic_err(e.to_string()))?
+            }
+            None => amount,
+        };
+        ALLOCATIONS.update(deps.storage, purchaser.clone(), |old| -> StdResult<_> {
+            Ok(old.unwrap_or_default() + allocation)
+        })?;
+        TOTAL_ALLOCATION.update(deps.storage, |total| -> StdResult<_> {
+            Ok(total + allocation)
+        })?;
+
+        PURCHASE_LIST.update(deps.storage, purchaser.clone(), |old| -> StdResult<_> {
+            Ok(old.unwrap_or_default() + amount)
+        })?;
+        PURCHASE_CHAINS.update(
+            deps.storage,
+            (purchaser.clone(), chain_id.clone()),
+            |old| -> StdResult<_> { Ok(old.unwrap_or_default() + amount) },
+        )?;
+        CHAIN_PURCHASED.update(deps.storage, chain_id.clone(), |old| -> StdResult<_> {
             Ok(old.unwrap_or_default() + amount)
         })?;
+        TOTAL_PURCHASED.update(deps.storage, |total| -> StdResult<_> { Ok(total + amount) })?;
+
+        let record_id = PURCHASE_RECORD_COUNT
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            + 1;
+        PURCHASE_RECORD_COUNT.save(deps.storage, &record_id)?;
+        let record = PurchaseRecord {
+            id: record_id,
+            purchaser,
+            chain_id,
+            round_id,
+            origin,
+            amount,
+            allocation,
+            recorded_at: env.block.time,
+        };
+        PURCHASE_RECORDS.save(deps.storage, record_id, &record)?;
+        Ok(record)
+    }
 
-        Ok(Response::new().add_attribute("action", "purchase"))
+    fn purchase_attributes(record: &PurchaseRecord) -> Vec<Attribute> {
+        let mut attributes = vec![
+            Attribute::new("record_id", record.id.to_string()),
+            Attribute::new("purchaser", record.purchaser.as_str()),
+            Attribute::new("chain_id", &record.chain_id),
+            Attribute::new("tx_hash", record.origin.tx_hash.to_hex()),
+            Attribute::new("log_index", record.origin.log_index.to_string()),
+            Attribute::new("payment_token", &record.origin.payment_token),
+            Attribute::new("amount", record.amount.to_string()),
+            Attribute::new("allocation", record.allocation.to_string()),
+        ];
+        if let Some(round_id) = record.round_id {
+            attributes.push(Attribute::new("round_id", round_id.to_string()));
+        }
+        attributes
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn adjust_purchase(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        chain_id: String,
+        round_id: Option<u64>,
+        purchaser: String,
+        delta: Int128,
+        reason: String,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        ensure_not_cancelled(&state)?;
+        if delta.is_zero() {
+            return Err(ContractError::ZeroAmount {});
+        }
+        if reason.trim().is_empty() {
+            return Err(ContractError::InvalidAdjustment {
+                reason: "reason must not be empty".to_string(),
+            });
+        }
+        load_chain_settings(deps.as_ref(), &chain_id)?;
+        let purchaser = parse_purchaser(deps.api, &purchaser)?;
+
+        let exceeds = || ContractError::InvalidAdjustment {
+            reason: "decrease exceeds purchased amount".to_string(),
+        };
+        let apply = |amount: Uint128| -> Result<Uint128, ContractError> {
+            if delta.is_negative() {
+                amount
+                    .checked_sub(delta.unsigned_abs())
+                    .map_err(|_| exceeds())
+            } else {
+                Ok(amount + delta.unsigned_abs())
+            }
+        };
+        if !delta.is_negative() {
+            ensure_below_caps(deps.as_ref(), &purchaser, delta.unsigned_abs())?;
+        }
+        let mut round_price = None;
+        if let Some(round_id) = round_id {
+            let mut round = load_round(deps.as_ref(), round_id)?;
+            round_price = Some(round.price);
+            round.total_purchased = apply(round.total_purchased)?;
+            if let Some(cap) = round.cap {
+                if !delta.is_negative() && round.total_purchased > cap {
+                    return Err(ContractError::ExceedsRoundCap { round_id, cap });
+                }
+            }
+            ROUNDS.save(deps.storage, round_id, &round)?;
+            let round_key = (round_id, purchaser.clone());
+            let round_amount = ROUND_PURCHASES
+                .may_load(deps.storage, round_key.clone())?
+                .unwrap_or_default();
+            ROUND_PURCHASES.save(deps.storage, round_key, &apply(round_amount)?)?;
+        }
+        let amount_before = PURCHASE_LIST
+            .may_load(deps.storage, purchaser.clone())?
+            .unwrap_or_default();
+        let amount_after = apply(amount_before)?;
+        let chain_key = (purchaser.clone(), chain_id.clone());
+        let chain_amount = PURCHASE_CHAINS
+            .may_load(deps.storage, chain_key.clone())?
+            .unwrap_or_default();
+        PURCHASE_CHAINS.save(deps.storage, chain_key, &apply(chain_amount)?)?;
+        let chain_total = CHAIN_PURCHASED
+            .may_load(deps.storage, chain_id.clone())?
+            .unwrap_or_default();
+        CHAIN_PURCHASED.save(deps.storage, chain_id.clone(), &apply(chain_total)?)?;
+        PURCHASE_LIST.save(deps.storage, purchaser.clone(), &amount_after)?;
+        TOTAL_PURCHASED.update(deps.storage, apply)?;
+
+        // Round adjustments move the allocation at the round's price. Otherwise it
+        // is scaled with the amount, keeping the purchaser's average price, and
+        // purchasers with nothing recorded are allocated at the flat terms.
+        let allocation_before = ALLOCATIONS
+            .may_load(deps.storage, purchaser.clone())?
+            .unwrap_or_default();
+        let allocation_after = match round_price {
+            Some(price) => {
+                let allocation_delta = delta
+                    .unsigned_abs()
+                    .checked_div_floor(price)
+                    .map_err(|e| StdError::generic_err(e.to_string()))?;
+                if delta.is_negative() {
+                    allocation_before
+                        .checked_sub(allocation_delta)
+                        .map_err(|_| ContractError::InvalidAdjustment {
+                            reason: "decrease exceeds allocation".to_string(),
+                        })?
+                } else {
+                    allocation_before + allocation_delta
+                }
+            }
+            None if amount_before.is_zero() => amount_after,
+            None => allocation_before.multiply_ratio(amount_after, amount_before),
+        };
+        ALLOCATIONS.save(deps.storage, purchaser.clone(), &allocation_after)?;
+        TOTAL_ALLOCATION.update(deps.storage, |total| -> StdResult<_> {
+            Ok(total - allocation_before + allocation_after)
+        })?;
+
+        let id = ADJUSTMENT_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        ADJUSTMENT_COUNT.save(deps.storage, &id)?;
+        let adjustment = PurchaseAdjustment {
+            id,
+            purchaser: purchaser.clone(),
+            chain_id,
+            round_id,
+            delta,
+            reason,
+            adjusted_by: info.sender,
+            amount_before,
+            amount_after,
+            adjusted_at: env.block.time,
+        };
+        ADJUSTMENTS.save(deps.storage, (purchaser, id), &adjustment)?;
+
+        let mut response = Response::new().add_attributes(vec![
+            ("action", "adjust_purchase"),
+            ("adjustment_id", id.to_string().as_str()),
+            ("purchaser", adjustment.purchaser.as_str()),
+            ("chain_id", adjustment.chain_id.as_str()),
+            ("delta", delta.to_string().as_str()),
+            ("amount", amount_after.to_string().as_str()),
+            ("allocation", allocation_after.to_string().as_str()),
+            ("reason", adjustment.reason.as_str()),
+        ]);
+        if let Some(round_id) = round_id {
+            response = response.add_attribute("round_id", round_id.to_string());
+        }
+        Ok(response)
+    }
+
+    fn validate_purchase_origin(origin: &PurchaseOrigin) -> Result<(), ContractError> {
+        if origin.tx_hash.len() != 32 {
+            return Err(ContractError::InvalidPurchaseOrigin {
+                reason: "tx_hash must be 32 bytes".to_string(),
+            });
+        }
+        evm_address(&origin.payment_token)?;
+        Ok(())
+    }
+
+    /// Relayers may retry, so each origin event is applied at most once.
+    fn ensure_not_duplicate(
+        deps: Deps,
+        chain_id: &str,
+        origin: &PurchaseOrigin,
+    ) -> Result<(), ContractError> {
+        if purchase_by_origin(deps, chain_id, &origin.tx_hash, origin.log_index)?.is_some() {
+            return Err(ContractError::DuplicatePurchase {
+                chain_id: chain_id.to_string(),
+                tx_hash: origin.tx_hash.to_hex(),
+                log_index: origin.log_index,
+            });
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn finalize(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         mint_amount: Uint128,
         distribute_amount: Uint128,
         pusd_amount: Uint128,
-    ) -> Result<Response, ContractError> {
+        treasury: String,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
         let mut state = STATE.load(deps.storage)?;
-        assert!(
-            state.owners.iter().any(|x| x == info.sender),
-            "Unauthorized"
-        );
-        assert!(
-            state.finished == false,
-            "The contract has already been finalized"
-        );
-        let messages = vec![
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        ensure_not_cancelled(&state)?;
+        if distribute_amount > mint_amount {
+            return Err(ContractError::DistributionExceedsMint {});
+        }
+        let treasury = deps.api.addr_validate(&treasury)?;
+        let total_purchased = TOTAL_PURCHASED.may_load(deps.storage)?.unwrap_or_default();
+        let sale_caps = SALE_CAPS.load(deps.storage)?;
+        if let Some(soft_cap) = sale_caps.soft_cap {
+            if total_purchased < soft_cap {
+                return Err(ContractError::SoftCapNotReached { soft_cap });
+            }
+        }
+        // A sold-out sale can be finalized before its window closes.
+        let sold_out = matches!(sale_caps.hard_cap, Some(hard_cap) if total_purchased >= hard_cap);
+        if env.block.time < state.end_time && !sold_out {
+            return Err(ContractError::SaleNotEnded {
+                end_time: state.end_time,
+            });
+        }
+
+        let token = TOKEN_METADATA.load(deps.storage)?;
+        let denom = format!("factory/{}/{}", env.contract.address, token.subdenom);
+        let distribution = Distribution {
+            denom: denom.clone(),
+            total_purchased,
+            total_allocation: TOTAL_ALLOCATION.may_load(deps.storage)?.unwrap_or_default(),
+            distribute_amount,
+            pusd_amount,
+            treasury: treasury.clone(),
+            distributed: Uint128::zero(),
+            processed: 0,
+            cursor: None,
+            completed: false,
+        };
+        DISTRIBUTION.save(deps.storage, &distribution)?;
+        state.finished = true;
+        STATE.save(deps.storage, &state)?;
+
+        // The distributed part is minted to the contract, which pays purchasers out
+        // of it in `ProcessDistribution`; the rest goes to the treasury.
+        let mut messages = vec![
             CosmosMsg::Custom(PalomaMsg::TokenFactoryMsg {
                 create_denom: Some(CreateDenomMsg {
-                    subdenom: subdenom.to_string(),
-                    metadata,
+                    subdenom: token.subdenom.clone(),
+                    metadata: denom_metadata(&token, &denom),
                 }),
                 mint_tokens: None,
             }),
@@ -122,27 +1037,406 @@ pub mod execute {
                 create_denom: None,
                 mint_tokens: Some(MintMsg {
                     denom: denom.clone(),
-                    amount: mint_amount,
-                    mint_to_address: denom_creator,
+                    amount: distribute_amount,
+                    mint_to_address: env.contract.address.to_string(),
                 }),
             }),
         ];
-        state.finished = true;
+        let treasury_amount = mint_amount - distribute_amount;
+        if !treasury_amount.is_zero() {
+            messages.push(CosmosMsg::Custom(PalomaMsg::TokenFactoryMsg {
+                create_denom: None,
+                mint_tokens: Some(MintMsg {
+                    denom: denom.clone(),
+                    amount: treasury_amount,
+                    mint_to_address: treasury.to_string(),
+                }),
+            }));
+        }
+        // Chains whose ERC20 was registered ahead of finalize are bound now.
+        for item in ERC20_ADDRESSES.range(deps.storage, None, None, Order::Ascending) {
+            let (chain_id, erc20_address) = item?;
+            messages.push(set_erc20_to_denom_msg(&denom, chain_id, erc20_address));
+        }
+        Ok(Response::new().add_messages(messages).add_attributes(vec![
+            ("action", "finalize"),
+            ("denom", denom.as_str()),
+            ("mint_amount", mint_amount.to_string().as_str()),
+            ("treasury", treasury.as_str()),
+            ("treasury_amount", treasury_amount.to_string().as_str()),
+            (
+                "total_purchased",
+                distribution.total_purchased.to_string().as_str(),
+            ),
+            ("pusd_amount", pusd_amount.to_string().as_str()),
+        ]))
+    }
+
+    pub fn process_distribution(
+        deps: DepsMut,
+        limit: Option<u32>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut distribution = DISTRIBUTION
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NotFinalized {})?;
+        if distribution.completed {
+            return Err(ContractError::DistributionCompleted {});
+        }
+
+        let limit = limit
+            .unwrap_or(DEFAULT_DISTRIBUTION_LIMIT)
+            .min(MAX_DISTRIBUTION_LIMIT) as usize;
+        let start = distribution.cursor.clone().map(Bound::exclusive);
+        let purchases = PURCHASE_LIST
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<(Purchaser, Uint128)>>>()?;
+        if purchases.len() < limit {
+            distribution.completed = true;
+        }
+
+        // Shares are rounded down; the rounding dust is sent to the treasury once
+        // every purchaser has been processed.
+        let mut messages = vec![];
+        for (purchaser, amount) in purchases {
+            distribution.processed += 1;
+            distribution.cursor = Some(purchaser.clone());
+            if amount.is_zero() {
+                continue;
+            }
+            let allocation = ALLOCATIONS
+                .may_load(deps.storage, purchaser.clone())?
+                .unwrap_or_default();
+            if allocation.is_zero() {
+                continue;
+            }
+            let share = distribution
+                .distribute_amount
+                .multiply_ratio(allocation, distribution.total_allocation);
+            if share.is_zero() {
+                continue;
+            }
+            distribution.distributed += share;
+            let address = match &purchaser {
+                Purchaser::Cosmos(addr) => {
+                    messages.push(CosmosMsg::Bank(BankMsg::Send {
+                        to_address: addr.to_string(),
+                        amount: vec![Coin::new(share, distribution.denom.clone())],
+                    }));
+                    continue;
+                }
+                Purchaser::Evm(address) => address.clone(),
+            };
+            // EVM purchasers are bridged their share on each chain they bought on,
+            // split by the amount purchased there.
+            let chain_amounts = PURCHASE_CHAINS
+                .prefix(purchaser.clone())
+                .range(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<(String, Uint128)>>>()?;
+            if chain_amounts.is_empty() {
+                return Err(ContractError::NoPurchaseChains {
+                    purchaser: purchaser.to_string(),
+                });
+            }
+            let mut remaining = share;
+            let last = chain_amounts.len().saturating_sub(1);
+            for (index, (chain_id, chain_amount)) in chain_amounts.into_iter().enumerate() {
+                let part = if index == last {
+                    remaining
+                } else {
+                    share.multiply_ratio(chain_amount, amount)
+                };
+                remaining -= part;
+                if part.is_zero() {
+                    continue;
+                }
+                if !ERC20_ADDRESSES.has(deps.storage, chain_id.clone()) {
+                    return Err(ContractError::TokenNotBridged { chain_id });
+                }
+                messages.push(CosmosMsg::Custom(PalomaMsg::SkywayMsg {
+                    send_tx: Some(SendTx {
+                        remote_chain_destination_address: address.clone(),
+                        amount: format!("{}{}", part, distribution.denom),
+                        chain_reference_id: chain_id,
+                    }),
+                    set_erc20_to_denom: None,
+                }));
+            }
+        }
+        DISTRIBUTION.save(deps.storage, &distribution)?;
+
+        let mut attributes = vec![
+            Attribute::new("action", "process_distribution"),
+            Attribute::new("processed", distribution.processed.to_string()),
+            Attribute::new("distributed", distribution.distributed.to_string()),
+            Attribute::new("completed", distribution.completed.to_string()),
+        ];
+        if distribution.completed {
+            let dust = distribution.distribute_amount - distribution.distributed;
+            if !dust.is_zero() {
+                messages.push(CosmosMsg::Bank(BankMsg::Send {
+                    to_address: distribution.treasury.to_string(),
+                    amount: vec![Coin::new(dust, distribution.denom.clone())],
+                }));
+            }
+            attributes.push(Attribute::new("dust", dust.to_string()));
+        }
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attributes(attributes))
+    }
+
+    pub fn bridge_token(
+        deps: DepsMut,
+        info: MessageInfo,
+        chain_id: String,
+        erc20_address: String,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        load_chain_settings(deps.as_ref(), &chain_id)?;
+        evm_address(&erc20_address)?;
+        ERC20_ADDRESSES.save(deps.storage, chain_id.clone(), &erc20_address)?;
+
+        let mut response = Response::new().add_attributes(vec![
+            ("action", "bridge_token"),
+            ("chain_id", chain_id.as_str()),
+            ("erc20_address", erc20_address.as_str()),
+        ]);
+        // Before finalize the denom does not exist yet; `finalize` binds it then.
+        if let Some(distribution) = DISTRIBUTION.may_load(deps.storage)? {
+            response = response.add_message(set_erc20_to_denom_msg(
+                &distribution.denom,
+                chain_id,
+                erc20_address,
+            ));
+        }
+        Ok(response)
+    }
+
+    pub fn pause(
+        deps: DepsMut,
+        info: MessageInfo,
+        scopes: Option<Vec<PauseScope>>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let mut status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+        match &scopes {
+            Some(scopes) => {
+                for scope in scopes {
+                    if !status.scopes.contains(scope) {
+                        status.scopes.push(*scope);
+                    }
+                }
+            }
+            None => status.all = true,
+        }
+        PAUSE_STATUS.save(deps.storage, &status)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "pause"),
+            ("scopes", scopes_attribute(scopes.as_deref()).as_str()),
+            ("paused_by", info.sender.as_str()),
+        ]))
+    }
+
+    pub fn unpause(
+        deps: DepsMut,
+        info: MessageInfo,
+        scopes: Option<Vec<PauseScope>>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let status = match &scopes {
+            Some(scopes) => {
+                let mut status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+                status.scopes.retain(|scope| !scopes.contains(scope));
+                status
+            }
+            None => PauseStatus::default(),
+        };
+        PAUSE_STATUS.save(deps.storage, &status)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "unpause"),
+            ("scopes", scopes_attribute(scopes.as_deref()).as_str()),
+        ]))
+    }
+
+    fn scopes_attribute(scopes: Option<&[PauseScope]>) -> String {
+        match scopes {
+            Some(scopes) => scopes
+                .iter()
+                .map(PauseScope::as_str)
+                .collect::<Vec<_>>()
+                .join(","),
+            None => "all".to_string(),
+        }
+    }
+
+    pub fn cancel_sale(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        ensure_not_cancelled(&state)?;
+        state.cancelled = true;
         STATE.save(deps.storage, &state)?;
-        Ok(Response::new().add_attribute("action", "finalize"))
+        Ok(Response::new().add_attribute("action", "cancel_sale"))
     }
 
-    pub fn refund(deps: DepsMut) -> Result<Response, ContractError> {
-        Ok(Response::new().add_attribute("action", "refund"))
+    pub fn refund(
+        deps: DepsMut,
+        info: MessageInfo,
+        purchasers: Option<Vec<String>>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        if !state.cancelled {
+            return Err(ContractError::SaleNotCancelled {});
+        }
+        // Owner batches skip purchasers with nothing left to refund, so a partially
+        // refunded list can simply be submitted again.
+        let batch = purchasers.is_some();
+        let purchasers = match purchasers {
+            Some(purchasers) => {
+                ensure_owner(&state, &info.sender)?;
+                purchasers
+                    .iter()
+                    .map(|purchaser| parse_purchaser(deps.api, purchaser))
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            None => vec![Purchaser::Cosmos(info.sender.clone())],
+        };
+
+        // EVM refunds are paid out of the source chain's refund wallet.
+        #[allow(deprecated)]
+        let contract: Contract = Contract {
+            constructor: None,
+            functions: BTreeMap::from_iter(vec![(
+                "refund".to_string(),
+                vec![Function {
+                    name: "refund".to_string(),
+                    inputs: vec![
+                        Param {
+                            name: "refund_wallet".to_string(),
+                            kind: ParamType::Address,
+                            internal_type: None,
+                        },
+                        Param {
+                            name: "recipient".to_string(),
+                            kind: ParamType::Address,
+                            internal_type: None,
+                        },
+                        Param {
+                            name: "amount".to_string(),
+                            kind: ParamType::Uint(256),
+                            internal_type: None,
+                        },
+                    ],
+                    outputs: Vec::new(),
+                    constant: None,
+                    state_mutability: StateMutability::NonPayable,
+                }],
+            )]),
+            events: BTreeMap::new(),
+            errors: BTreeMap::new(),
+            receive: false,
+            fallback: false,
+        };
+
+        let mut response = Response::new().add_attribute("action", "refund");
+        for purchaser in purchasers {
+            if REFUNDED.has(deps.storage, purchaser.clone()) {
+                if batch {
+                    response = response.add_attribute("already_refunded", purchaser.as_str());
+                    continue;
+                }
+                return Err(ContractError::AlreadyRefunded {
+                    purchaser: purchaser.to_string(),
+                });
+            }
+            let amount = PURCHASE_LIST
+                .may_load(deps.storage, purchaser.clone())?
+                .unwrap_or_default();
+            if amount.is_zero() {
+                if batch {
+                    response = response.add_attribute("nothing_to_refund", purchaser.as_str());
+                    continue;
+                }
+                return Err(ContractError::NothingToRefund {
+                    purchaser: purchaser.to_string(),
+                });
+            }
+            let chain_amounts = PURCHASE_CHAINS
+                .prefix(purchaser.clone())
+                .range(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<(String, Uint128)>>>()?;
+            if chain_amounts.is_empty() {
+                return Err(ContractError::NoPurchaseChains {
+                    purchaser: purchaser.to_string(),
+                });
+            }
+            for (chain_id, chain_amount) in chain_amounts {
+                if chain_amount.is_zero() {
+                    continue;
+                }
+                let chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
+                let message = match &purchaser {
+                    // Bech32 purchasers have no address on the source chain.
+                    Purchaser::Cosmos(addr) => {
+                        let denom = chain_settings.refund_denom.clone().ok_or_else(|| {
+                            ContractError::RefundDenomNotSet {
+                                chain_id: chain_id.clone(),
+                            }
+                        })?;
+                        CosmosMsg::Bank(BankMsg::Send {
+                            to_address: addr.to_string(),
+                            amount: vec![Coin::new(chain_amount, denom)],
+                        })
+                    }
+                    Purchaser::Evm(address) => CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
+                        create_job: None,
+                        execute_job: Some(ExecuteJob {
+                            job_id: chain_settings.main_job_id.clone(),
+                            payload: Binary::new(contract.function("refund")?.encode_input(&[
+                                Token::Address(evm_address(&chain_settings.refund_wallet)?),
+                                Token::Address(evm_address(address)?),
+                                Token::Uint(Uint::from(chain_amount.u128())),
+                            ])?),
+                        }),
+                        update_job: None,
+                    }),
+                };
+                CHAIN_PURCHASED.update(
+                    deps.storage,
+                    chain_id.clone(),
+                    |total| -> StdResult<_> {
+                        Ok(total.unwrap_or_default().saturating_sub(chain_amount))
+                    },
+                )?;
+                response = response.add_message(message).add_attributes(vec![
+                    ("purchaser", purchaser.as_str()),
+                    ("chain_id", chain_id.as_str()),
+                    ("refund_wallet", chain_settings.refund_wallet.as_str()),
+                    ("amount", chain_amount.to_string().as_str()),
+                ]);
+            }
+            PURCHASE_LIST.save(deps.storage, purchaser.clone(), &Uint128::zero())?;
+            TOTAL_PURCHASED.update(deps.storage, |total| -> StdResult<_> { Ok(total - amount) })?;
+            REFUNDED.save(deps.storage, purchaser, &amount)?;
+        }
+        Ok(response)
     }
 
-    pub fn set_paloma(deps: DepsMut, chain_id: String) -> Result<Response, ContractError> {
-        // ACTION: Implement SetPaloma
+    pub fn set_paloma(
+        deps: DepsMut,
+        info: MessageInfo,
+        chain_id: String,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
         let state = STATE.load(deps.storage)?;
-        assert!(
-            state.owners.iter().any(|x| x == info.sender),
-            "Unauthorized"
-        );
+        ensure_owner(&state, &info.sender)?;
+        let chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
 
         #[allow(deprecated)]
         let contract: Contract = Contract {
@@ -164,32 +1458,177 @@ pub mod execute {
         };
         Ok(Response::new()
             .add_message(CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
-                execute_job: ExecuteJob {
-                    job_id: CHAIN_SETTINGS
-                        .load(deps.storage, chain_id.clone())?
-                        .main_job_id,
-                    payload: Binary::new(
-                        contract
-                            .function("set_paloma")
-                            .unwrap()
-                            .encode_input(&[])
-                            .unwrap(),
-                    ),
-                },
+                create_job: None,
+                execute_job: Some(ExecuteJob {
+                    job_id: chain_settings.main_job_id.clone(),
+                    payload: Binary::new(contract.function("set_paloma")?.encode_input(&[])?),
+                }),
+                update_job: None,
             }))
             .add_attribute("action", "set_paloma"))
     }
 
-    pub fn update_compass(
+    pub fn queue_change(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        chain_id: Option<String>,
+        change: TimelockedChange,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        if let Some(chain_id) = &chain_id {
+            load_chain_settings(deps.as_ref(), chain_id)?;
+        }
+        match &change {
+            TimelockedChange::Compass {
+                new_compass: address,
+            }
+            | TimelockedChange::RefundWallet {
+                new_refund_wallet: address,
+            }
+            | TimelockedChange::ServiceFeeCollector {
+                new_service_fee_collector: address,
+            } => {
+                evm_address(address)?;
+            }
+            TimelockedChange::ChainSettings { chain_settings } => {
+                validate_chain_settings(chain_settings)?;
+            }
+            TimelockedChange::GasFee { .. }
+            | TimelockedChange::ServiceFee { .. }
+            | TimelockedChange::CreateJob { .. }
+            | TimelockedChange::UpdateJob { .. }
+            | TimelockedChange::TimelockDelay { .. } => {}
+        }
+        let id = QUEUED_CHANGE_COUNT
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            + 1;
+        let queued = QueuedChange {
+            id,
+            chain_id,
+            change,
+            eta: env.block.time.plus_seconds(state.timelock_delay),
+            queued_by: info.sender,
+        };
+        QUEUED_CHANGE_COUNT.save(deps.storage, &id)?;
+        QUEUED_CHANGES.save(deps.storage, id, &queued)?;
+        let mut response = Response::new().add_attributes(vec![
+            ("action", "queue_change"),
+            ("change_id", id.to_string().as_str()),
+            ("eta", queued.eta.to_string().as_str()),
+        ]);
+        if let Some(chain_id) = &queued.chain_id {
+            response = response.add_attribute("chain_id", chain_id);
+        }
+        Ok(response)
+    }
+
+    pub fn execute_queued_change(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        change_id: u64,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let queued = QUEUED_CHANGES
+            .may_load(deps.storage, change_id)?
+            .ok_or(ContractError::QueuedChangeNotFound { change_id })?;
+        if env.block.time < queued.eta {
+            return Err(ContractError::TimelockNotExpired {
+                change_id,
+                eta: queued.eta,
+            });
+        }
+        QUEUED_CHANGES.remove(deps.storage, change_id);
+        // Only `TimelockDelay` is queued without a chain.
+        let chain_id = queued.chain_id.unwrap_or_default();
+        let response = match queued.change {
+            TimelockedChange::Compass { new_compass } => {
+                update_compass(deps, chain_id, new_compass)?
+            }
+            TimelockedChange::RefundWallet { new_refund_wallet } => {
+                update_refund_wallet(deps, chain_id, new_refund_wallet)?
+            }
+            TimelockedChange::GasFee { new_gas_fee } => {
+                update_gas_fee(deps, chain_id, new_gas_fee)?
+            }
+            TimelockedChange::ServiceFeeCollector {
+                new_service_fee_collector,
+            } => update_service_fee_collector(deps, chain_id, new_service_fee_collector)?,
+            TimelockedChange::ServiceFee { new_service_fee } => {
+                update_service_fee(deps, chain_id, new_service_fee)?
+            }
+            TimelockedChange::ChainSettings { chain_settings } => {
+                update_chain(deps, chain_id, chain_settings)?
+            }
+            TimelockedChange::CreateJob {
+                job_id,
+                definition,
+                payload,
+                payload_modifiable,
+                is_mev,
+                permissions,
+            } => create_job(
+                deps,
+                chain_id,
+                job_id,
+                definition,
+                payload,
+                payload_modifiable,
+                is_mev,
+                permissions,
+            )?,
+            TimelockedChange::UpdateJob {
+                definition,
+                payload,
+                permissions,
+            } => update_job(deps, chain_id, definition, payload, permissions)?,
+            TimelockedChange::TimelockDelay { new_delay } => {
+                update_timelock_delay(deps, new_delay)?
+            }
+        };
+        Ok(response.add_attribute("change_id", change_id.to_string()))
+    }
+
+    pub fn cancel_queued_change(
+        deps: DepsMut,
+        info: MessageInfo,
+        change_id: u64,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        if !QUEUED_CHANGES.has(deps.storage, change_id) {
+            return Err(ContractError::QueuedChangeNotFound { change_id });
+        }
+        QUEUED_CHANGES.remove(deps.storage, change_id);
+        Ok(Response::new().add_attributes(vec![
+            ("action", "cancel_queued_change"),
+            ("change_id", change_id.to_string().as_str()),
+        ]))
+    }
+
+    fn update_timelock_delay(
+        deps: DepsMut,
+        delay: u64,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+        state.timelock_delay = delay;
+        STATE.save(deps.storage, &state)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "update_timelock_delay"),
+            ("delay", delay.to_string().as_str()),
+        ]))
+    }
+
+    fn update_compass(
         deps: DepsMut,
         chain_id: String,
         new_compass: String,
-    ) -> Result<Response, ContractError> {
-        let state = STATE.load(deps.storage)?;
-        assert!(
-            state.owners.iter().any(|x| x == info.sender),
-            "Unauthorized"
-        );
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
 
         #[allow(deprecated)]
         let contract: Contract = Contract {
@@ -213,23 +1652,19 @@ pub mod execute {
             receive: false,
             fallback: false,
         };
-        let tokens = &[Token::Address(
-            Address::from_str(new_compass.as_str()).unwrap(),
-        )];
+        let tokens = &[Token::Address(evm_address(&new_compass)?)];
+        chain_settings.compass = new_compass.clone();
+        CHAIN_SETTINGS.save(deps.storage, chain_id.clone(), &chain_settings)?;
         Ok(Response::new()
             .add_message(CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
-                execute_job: ExecuteJob {
-                    job_id: CHAIN_SETTINGS
-                        .load(deps.storage, chain_id.clone())?
-                        .main_job_id,
+                create_job: None,
+                execute_job: Some(ExecuteJob {
+                    job_id: chain_settings.main_job_id.clone(),
                     payload: Binary::new(
-                        contract
-                            .function("update_compass")
-                            .unwrap()
-                            .encode_input(tokens)
-                            .unwrap(),
+                        contract.function("update_compass")?.encode_input(tokens)?,
                     ),
-                },
+                }),
+                update_job: None,
             }))
             .add_attributes(vec![
                 ("action", "update_compass"),
@@ -238,18 +1673,13 @@ pub mod execute {
             ]))
     }
 
-    pub fn update_refund_wallet(
+    fn update_refund_wallet(
         deps: DepsMut,
         chain_id: String,
         new_refund_wallet: String,
-    ) -> Result<Response, ContractError> {
-        let state = STATE.load(deps.storage)?;
-        assert!(
-            state.owners.iter().any(|x| x == info.sender),
-            "Unauthorized"
-        );
-        let update_refund_wallet_address: Address =
-            Address::from_str(new_refund_wallet.as_str()).unwrap();
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
+        let update_refund_wallet_address: Address = evm_address(&new_refund_wallet)?;
         #[allow(deprecated)]
         let contract: Contract = Contract {
             constructor: None,
@@ -272,34 +1702,30 @@ pub mod execute {
             receive: false,
             fallback: false,
         };
+        chain_settings.refund_wallet = new_refund_wallet;
+        CHAIN_SETTINGS.save(deps.storage, chain_id.clone(), &chain_settings)?;
         Ok(Response::new()
             .add_message(CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
-                execute_job: ExecuteJob {
-                    job_id: CHAIN_SETTINGS
-                        .load(deps.storage, chain_id.clone())?
-                        .main_job_id,
+                create_job: None,
+                execute_job: Some(ExecuteJob {
+                    job_id: chain_settings.main_job_id.clone(),
                     payload: Binary::new(
                         contract
-                            .function("update_refund_wallet")
-                            .unwrap()
-                            .encode_input(&[Token::Address(update_refund_wallet_address)])
-                            .unwrap(),
+                            .function("update_refund_wallet")?
+                            .encode_input(&[Token::Address(update_refund_wallet_address)])?,
                     ),
-                },
+                }),
+                update_job: None,
             }))
             .add_attribute("action", "update_refund_wallet"))
     }
 
-    pub fn update_gas_fee(
+    fn update_gas_fee(
         deps: DepsMut,
         chain_id: String,
         new_gas_fee: Uint256,
-    ) -> Result<Response, ContractError> {
-        let state = STATE.load(deps.storage)?;
-        assert!(
-            state.owners.iter().any(|x| x == info.sender),
-            "Unauthorized"
-        );
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
         #[allow(deprecated)]
         let contract: Contract = Contract {
             constructor: None,
@@ -322,38 +1748,30 @@ pub mod execute {
             receive: false,
             fallback: false,
         };
+        chain_settings.gas_fee = new_gas_fee;
+        CHAIN_SETTINGS.save(deps.storage, chain_id.clone(), &chain_settings)?;
         Ok(Response::new()
             .add_message(CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
-                execute_job: ExecuteJob {
-                    job_id: CHAIN_SETTINGS
-                        .load(deps.storage, chain_id.clone())?
-                        .main_job_id,
-                    payload: Binary::new(
-                        contract
-                            .function("update_gas_fee")
-                            .unwrap()
-                            .encode_input(&[Token::Uint(Uint::from_big_endian(
-                                &new_gas_fee.to_be_bytes(),
-                            ))])
-                            .unwrap(),
-                    ),
-                },
+                create_job: None,
+                execute_job: Some(ExecuteJob {
+                    job_id: chain_settings.main_job_id.clone(),
+                    payload: Binary::new(contract.function("update_gas_fee")?.encode_input(&[
+                        Token::Uint(Uint::from_big_endian(&new_gas_fee.to_be_bytes())),
+                    ])?),
+                }),
+                update_job: None,
             }))
             .add_attribute("action", "update_gas_fee"))
     }
 
-    pub fn update_service_fee_collector(
+    fn update_service_fee_collector(
         deps: DepsMut,
         chain_id: String,
         new_service_fee_collector: String,
-    ) -> Result<Response, ContractError> {
-        let state = STATE.load(deps.storage)?;
-        assert!(
-            state.owners.iter().any(|x| x == info.sender),
-            "Unauthorized"
-        );
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
         let update_service_fee_collector_address: Address =
-            Address::from_str(new_service_fee_collector.as_str()).unwrap();
+            evm_address(&new_service_fee_collector)?;
         #[allow(deprecated)]
         let contract: Contract = Contract {
             constructor: None,
@@ -376,36 +1794,32 @@ pub mod execute {
             receive: false,
             fallback: false,
         };
+        chain_settings.service_fee_collector = new_service_fee_collector;
+        CHAIN_SETTINGS.save(deps.storage, chain_id.clone(), &chain_settings)?;
         Ok(Response::new()
             .add_message(CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
-                execute_job: ExecuteJob {
-                    job_id: CHAIN_SETTINGS
-                        .load(deps.storage, chain_id.clone())?
-                        .main_job_id,
+                create_job: None,
+                execute_job: Some(ExecuteJob {
+                    job_id: chain_settings.main_job_id.clone(),
                     payload: Binary::new(
                         contract
-                            .function("update_service_fee_collector")
-                            .unwrap()
+                            .function("update_service_fee_collector")?
                             .encode_input(&[Token::Address(
                                 update_service_fee_collector_address,
-                            )])
-                            .unwrap(),
+                            )])?,
                     ),
-                },
+                }),
+                update_job: None,
             }))
             .add_attribute("action", "update_service_fee_collector"))
     }
-    
-    pub fn update_service_fee(
+
+    fn update_service_fee(
         deps: DepsMut,
         chain_id: String,
         new_service_fee: Uint256,
-    ) -> Result<Response, ContractError> {
-        let state = STATE.load(deps.storage)?;
-        assert!(
-            state.owners.iter().any(|x| x == info.sender),
-            "Unauthorized"
-        );
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
         #[allow(deprecated)]
         let contract: Contract = Contract {
             constructor: None,
@@ -428,42 +1842,1458 @@ pub mod execute {
             receive: false,
             fallback: false,
         };
+        chain_settings.service_fee = new_service_fee;
+        CHAIN_SETTINGS.save(deps.storage, chain_id.clone(), &chain_settings)?;
         Ok(Response::new()
             .add_message(CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
-                execute_job: ExecuteJob {
-                    job_id: CHAIN_SETTINGS
-                        .load(deps.storage, chain_id.clone())?
-                        .main_job_id,
-                    payload: Binary::new(
-                        contract
-                            .function("update_service_fee")
-                            .unwrap()
-                            .encode_input(&[Token::Uint(Uint::from_big_endian(
-                                &new_service_fee.to_be_bytes(),
-                            ))])
-                            .unwrap(),
-                    ),
-                },
+                create_job: None,
+                execute_job: Some(ExecuteJob {
+                    job_id: chain_settings.main_job_id.clone(),
+                    payload: Binary::new(contract.function("update_service_fee")?.encode_input(
+                        &[Token::Uint(Uint::from_big_endian(
+                            &new_service_fee.to_be_bytes(),
+                        ))],
+                    )?),
+                }),
+                update_job: None,
             }))
             .add_attribute("action", "update_service_fee"))
     }
 
+    pub fn register_chain(
+        deps: DepsMut,
+        info: MessageInfo,
+        chain_id: String,
+        chain_settings: ChainSettings,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        if CHAIN_SETTINGS.has(deps.storage, chain_id.clone()) {
+            return Err(ContractError::ChainAlreadyRegistered { chain_id });
+        }
+        validate_chain_settings(&chain_settings)?;
+        CHAIN_SETTINGS.save(deps.storage, chain_id.clone(), &chain_settings)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "register_chain"),
+            ("chain_id", &chain_id),
+            ("main_job_id", &chain_settings.main_job_id),
+        ]))
+    }
+
+    fn update_chain(
+        deps: DepsMut,
+        chain_id: String,
+        chain_settings: ChainSettings,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        load_chain_settings(deps.as_ref(), &chain_id)?;
+        CHAIN_SETTINGS.save(deps.storage, chain_id.clone(), &chain_settings)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "update_chain"),
+            ("chain_id", &chain_id),
+            ("main_job_id", &chain_settings.main_job_id),
+        ]))
+    }
+
+    pub fn remove_chain(
+        deps: DepsMut,
+        info: MessageInfo,
+        chain_id: String,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        load_chain_settings(deps.as_ref(), &chain_id)?;
+        let amount = CHAIN_PURCHASED
+            .may_load(deps.storage, chain_id.clone())?
+            .unwrap_or_default();
+        if !amount.is_zero() {
+            return Err(ContractError::ChainHasPurchases { chain_id, amount });
+        }
+        for item in QUEUED_CHANGES.range(deps.storage, None, None, Order::Ascending) {
+            let (change_id, queued) = item?;
+            if queued.chain_id.as_ref() == Some(&chain_id) {
+                return Err(ContractError::ChainHasQueuedChanges {
+                    chain_id,
+                    change_id,
+                });
+            }
+        }
+        CHAIN_SETTINGS.remove(deps.storage, chain_id.clone());
+        // Otherwise finalize would still bind the denom on the removed chain.
+        ERC20_ADDRESSES.remove(deps.storage, chain_id.clone());
+        Ok(Response::new()
+            .add_attributes(vec![("action", "remove_chain"), ("chain_id", &chain_id)]))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_job(
+        deps: DepsMut,
+        chain_id: String,
+        job_id: String,
+        definition: JobDefinition,
+        payload: Binary,
+        payload_modifiable: bool,
+        is_mev: bool,
+        permissions: JobPermissions,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
+        chain_settings.main_job_id = job_id.clone();
+        CHAIN_SETTINGS.save(deps.storage, chain_id.clone(), &chain_settings)?;
+        Ok(Response::new()
+            .add_message(CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
+                create_job: Some(CreateJob {
+                    job_id: job_id.clone(),
+                    chain_type: chain_settings.chain_type,
+                    chain_reference_id: chain_id.clone(),
+                    definition,
+                    payload,
+                    payload_modifiable,
+                    is_mev,
+                    permissions,
+                }),
+                execute_job: None,
+                update_job: None,
+            }))
+            .add_attributes(vec![
+                ("action", "create_job"),
+                ("chain_id", &chain_id),
+                ("job_id", &job_id),
+            ]))
+    }
+
+    fn update_job(
+        deps: DepsMut,
+        chain_id: String,
+        definition: Option<JobDefinition>,
+        payload: Option<Binary>,
+        permissions: Option<JobPermissions>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let chain_settings = load_chain_settings(deps.as_ref(), &chain_id)?;
+        Ok(Response::new()
+            .add_message(CosmosMsg::Custom(PalomaMsg::SchedulerMsg {
+                create_job: None,
+                execute_job: None,
+                update_job: Some(UpdateJob {
+                    job_id: chain_settings.main_job_id.clone(),
+                    definition,
+                    payload,
+                    permissions,
+                }),
+            }))
+            .add_attributes(vec![
+                ("action", "update_job"),
+                ("chain_id", &chain_id),
+                ("job_id", &chain_settings.main_job_id),
+            ]))
+    }
+
+    pub fn add_owner(
+        deps: DepsMut,
+        info: MessageInfo,
+        owner: String,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let owner = deps.api.addr_validate(&owner)?;
+        if state.owners.contains(&owner) {
+            return Err(ContractError::AlreadyOwner {
+                owner: owner.to_string(),
+            });
+        }
+        state.owners.push(owner.clone());
+        STATE.save(deps.storage, &state)?;
+        Ok(Response::new()
+            .add_attribute("action", "add_owner")
+            .add_event(Event::new("owner_added").add_attribute("owner", owner)))
+    }
+
+    pub fn remove_owner(
+        deps: DepsMut,
+        info: MessageInfo,
+        owner: String,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let owner = deps.api.addr_validate(&owner)?;
+        if !state.owners.contains(&owner) {
+            return Err(ContractError::NotOwner {
+                address: owner.to_string(),
+            });
+        }
+        if state.owners.len() == 1 {
+            return Err(ContractError::LastOwner {});
+        }
+        state.owners.retain(|x| x != owner);
+        ensure_valid_threshold(&state)?;
+        STATE.save(deps.storage, &state)?;
+        Ok(Response::new()
+            .add_attribute("action", "remove_owner")
+            .add_event(Event::new("owner_removed").add_attribute("owner", owner)))
+    }
+
+    pub fn propose_owner(
+        deps: DepsMut,
+        info: MessageInfo,
+        new_owner: String,
+        replacing: Option<String>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let new_owner = deps.api.addr_validate(&new_owner)?;
+        if state.owners.contains(&new_owner) {
+            return Err(ContractError::AlreadyOwner {
+                owner: new_owner.to_string(),
+            });
+        }
+        let replacing = replacing
+            .map(|replacing| deps.api.addr_validate(&replacing))
+            .transpose()?;
+        if let Some(replacing) = &replacing {
+            if !state.owners.contains(replacing) {
+                return Err(ContractError::NotOwner {
+                    address: replacing.to_string(),
+                });
+            }
+        }
+        PENDING_OWNER.save(
+            deps.storage,
+            &PendingOwner {
+                proposer: info.sender.clone(),
+                new_owner: new_owner.clone(),
+                replacing: replacing.clone(),
+            },
+        )?;
+        let mut event = Event::new("ownership_proposed")
+            .add_attribute("proposer", info.sender)
+            .add_attribute("new_owner", new_owner);
+        if let Some(replacing) = replacing {
+            event = event.add_attribute("replacing", replacing);
+        }
+        Ok(Response::new()
+            .add_attribute("action", "propose_owner")
+            .add_event(event))
+    }
+
+    pub fn accept_ownership(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let pending = PENDING_OWNER
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoPendingOwner {})?;
+        if pending.new_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        let mut state = STATE.load(deps.storage)?;
+        // The replaced owner's seat goes to the new owner; if they were removed
+        // in the meantime the new owner simply joins.
+        if let Some(replacing) = &pending.replacing {
+            state.owners.retain(|x| x != replacing);
+        }
+        if !state.owners.contains(&pending.new_owner) {
+            state.owners.push(pending.new_owner.clone());
+        }
+        STATE.save(deps.storage, &state)?;
+        PENDING_OWNER.remove(deps.storage);
+        let mut event =
+            Event::new("ownership_transferred").add_attribute("new_owner", pending.new_owner);
+        if let Some(replacing) = pending.replacing {
+            event = event.add_attribute("previous_owner", replacing);
+        }
+        Ok(Response::new()
+            .add_attribute("action", "accept_ownership")
+            .add_event(event))
+    }
+
+    pub fn update_threshold(
+        deps: DepsMut,
+        info: MessageInfo,
+        threshold: u32,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        state.threshold = threshold;
+        ensure_valid_threshold(&state)?;
+        STATE.save(deps.storage, &state)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "update_threshold"),
+            ("threshold", threshold.to_string().as_str()),
+        ]))
+    }
+
+    pub fn propose(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg,
+        expires_in: Option<u64>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        if !msg.requires_proposal() {
+            return Err(ContractError::NotProposable {});
+        }
+        let id = PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        let proposal = Proposal {
+            id,
+            proposer: info.sender.clone(),
+            msg,
+            approvals: vec![info.sender],
+            expires_at: env
+                .block
+                .time
+                .plus_seconds(expires_in.unwrap_or(DEFAULT_PROPOSAL_DURATION)),
+            status: ProposalStatus::Open,
+        };
+        PROPOSAL_COUNT.save(deps.storage, &id)?;
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "propose"),
+            ("proposal_id", id.to_string().as_str()),
+            ("expires_at", proposal.expires_at.to_string().as_str()),
+        ]))
+    }
+
+    pub fn vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        proposal_id: u64,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let mut proposal = load_open_proposal(deps.as_ref(), &env, proposal_id)?;
+        if proposal.approvals.contains(&info.sender) {
+            return Err(ContractError::AlreadyVoted { proposal_id });
+        }
+        proposal.approvals.push(info.sender);
+        PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "vote"),
+            ("proposal_id", proposal_id.to_string().as_str()),
+            ("approvals", proposal.approvals.len().to_string().as_str()),
+        ]))
+    }
+
+    pub fn execute_proposal(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        proposal_id: u64,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        let mut proposal = load_open_proposal(deps.as_ref(), &env, proposal_id)?;
+        // Approvals from owners removed since voting no longer count.
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|x| state.owners.contains(x))
+            .count();
+        if approvals < state.threshold as usize {
+            return Err(ContractError::ThresholdNotReached { proposal_id });
+        }
+        proposal.status = ProposalStatus::Executed;
+        PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+        let response = dispatch(deps, env, info, proposal.msg)?;
+        Ok(response.add_attributes(vec![
+            ("proposal_action", "execute_proposal"),
+            ("proposal_id", proposal_id.to_string().as_str()),
+        ]))
+    }
+
+    pub fn cancel_proposal(
+        deps: DepsMut,
+        info: MessageInfo,
+        proposal_id: u64,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        // Owners removed since proposing lose the right to cancel.
+        ensure_owner(&STATE.load(deps.storage)?, &info.sender)?;
+        let mut proposal = PROPOSALS
+            .may_load(deps.storage, proposal_id)?
+            .ok_or(ContractError::ProposalNotFound { proposal_id })?;
+        if proposal.proposer != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if proposal.status != ProposalStatus::Open {
+            return Err(ContractError::ProposalNotOpen { proposal_id });
+        }
+        proposal.status = ProposalStatus::Cancelled;
+        PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "cancel_proposal"),
+            ("proposal_id", proposal_id.to_string().as_str()),
+        ]))
+    }
+
+    fn load_open_proposal(
+        deps: Deps,
+        env: &Env,
+        proposal_id: u64,
+    ) -> Result<Proposal, ContractError> {
+        let proposal = PROPOSALS
+            .may_load(deps.storage, proposal_id)?
+            .ok_or(ContractError::ProposalNotFound { proposal_id })?;
+        if proposal.status != ProposalStatus::Open {
+            return Err(ContractError::ProposalNotOpen { proposal_id });
+        }
+        if env.block.time >= proposal.expires_at {
+            return Err(ContractError::ProposalExpired { proposal_id });
+        }
+        Ok(proposal)
+    }
+
+    pub fn extend_sale_window(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        end_time: Timestamp,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        ensure_not_cancelled(&state)?;
+        if env.block.time >= state.end_time {
+            return Err(ContractError::SaleEnded {
+                end_time: state.end_time,
+            });
+        }
+        if end_time <= state.end_time {
+            return Err(ContractError::InvalidSaleWindow {
+                reason: "end_time can only be extended".to_string(),
+            });
+        }
+        state.end_time = end_time;
+        STATE.save(deps.storage, &state)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "extend_sale_window"),
+            ("end_time", end_time.to_string().as_str()),
+        ]))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_round(
+        deps: DepsMut,
+        info: MessageInfo,
+        name: String,
+        price: Decimal,
+        cap: Option<Uint128>,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        allowlist_only: bool,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        ensure_not_cancelled(&state)?;
+        if price.is_zero() {
+            return Err(ContractError::InvalidRound {
+                reason: "price must be greater than zero".to_string(),
+            });
+        }
+        if start_time >= end_time {
+            return Err(ContractError::InvalidRound {
+                reason: "end_time must be after start_time".to_string(),
+            });
+        }
+        if start_time < state.start_time || end_time > state.end_time {
+            return Err(ContractError::InvalidRound {
+                reason: "round must lie within the sale window".to_string(),
+            });
+        }
+        let id = ROUND_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        let round = Round {
+            id,
+            name,
+            price,
+            cap,
+            start_time,
+            end_time,
+            allowlist_only,
+            merkle_root: None,
+            open: false,
+            total_purchased: Uint128::zero(),
+        };
+        ROUND_COUNT.save(deps.storage, &id)?;
+        ROUNDS.save(deps.storage, id, &round)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "create_round"),
+            ("round_id", id.to_string().as_str()),
+            ("name", round.name.as_str()),
+            ("price", price.to_string().as_str()),
+        ]))
+    }
+
+    pub fn set_round_open(
+        deps: DepsMut,
+        info: MessageInfo,
+        round_id: u64,
+        open: bool,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        let mut round = load_round(deps.as_ref(), round_id)?;
+        round.open = open;
+        ROUNDS.save(deps.storage, round_id, &round)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", if open { "open_round" } else { "close_round" }),
+            ("round_id", round_id.to_string().as_str()),
+        ]))
+    }
+
+    pub fn update_round_allowlist(
+        deps: DepsMut,
+        info: MessageInfo,
+        round_id: u64,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        load_round(deps.as_ref(), round_id)?;
+        for purchaser in &add {
+            let purchaser = parse_purchaser(deps.api, purchaser)?;
+            ROUND_ALLOWLIST.save(deps.storage, (round_id, purchaser), &true)?;
+        }
+        for purchaser in &remove {
+            let purchaser = parse_purchaser(deps.api, purchaser)?;
+            ROUND_ALLOWLIST.remove(deps.storage, (round_id, purchaser));
+        }
+        Ok(Response::new().add_attributes(vec![
+            ("action", "update_round_allowlist"),
+            ("round_id", round_id.to_string().as_str()),
+            ("added", add.len().to_string().as_str()),
+            ("removed", remove.len().to_string().as_str()),
+        ]))
+    }
+
+    fn load_round(deps: Deps, round_id: u64) -> Result<Round, ContractError> {
+        ROUNDS
+            .may_load(deps.storage, round_id)?
+            .ok_or(ContractError::RoundNotFound { round_id })
+    }
+
+    fn ensure_round_open(
+        deps: Deps,
+        env: &Env,
+        round: &Round,
+        purchaser: &Purchaser,
+        amount: Uint128,
+        allocation_proof: Option<&AllocationProof>,
+    ) -> Result<(), ContractError> {
+        let round_id = round.id;
+        if !round.open || env.block.time < round.start_time || env.block.time >= round.end_time {
+            return Err(ContractError::RoundNotOpen { round_id });
+        }
+        if let Some(merkle_root) = &round.merkle_root {
+            let allocation_proof =
+                allocation_proof.ok_or(ContractError::MissingAllocationProof { round_id })?;
+            if !verify_allocation_proof(merkle_root, purchaser, allocation_proof) {
+                return Err(ContractError::InvalidAllocationProof {
+                    round_id,
+                    purchaser: purchaser.to_string(),
+                });
+            }
+            let purchased = ROUND_PURCHASES
+                .may_load(deps.storage, (round_id, purchaser.clone()))?
+                .unwrap_or_default();
+            if purchased + amount > allocation_proof.max_allocation {
+                return Err(ContractError::ExceedsMaxAllocation {
+                    purchaser: purchaser.to_string(),
+                    max_allocation: allocation_proof.max_allocation,
+                });
+            }
+        } else if round.allowlist_only
+            && !ROUND_ALLOWLIST.has(deps.storage, (round_id, purchaser.clone()))
+        {
+            return Err(ContractError::NotAllowlisted {
+                round_id,
+                purchaser: purchaser.to_string(),
+            });
+        }
+        if let Some(cap) = round.cap {
+            if round.total_purchased + amount > cap {
+                return Err(ContractError::ExceedsRoundCap { round_id, cap });
+            }
+        }
+        Ok(())
+    }
+
+    /// Leaves hash the canonical purchaser string followed by the allocation.
+    fn verify_allocation_proof(
+        merkle_root: &HexBinary,
+        purchaser: &Purchaser,
+        allocation_proof: &AllocationProof,
+    ) -> bool {
+        let leaf = format!("{}{}", purchaser, allocation_proof.max_allocation);
+        let mut hash: [u8; 32] = Sha256::digest(leaf.as_bytes()).into();
+        for sibling in &allocation_proof.proof {
+            let Ok(sibling) = <[u8; 32]>::try_from(sibling.as_slice()) else {
+                return false;
+            };
+            let (first, second) = if hash <= sibling {
+                (hash, sibling)
+            } else {
+                (sibling, hash)
+            };
+            hash = Sha256::new()
+                .chain_update(first)
+                .chain_update(second)
+                .finalize()
+                .into();
+        }
+        merkle_root.as_slice() == hash.as_slice()
+    }
+
+    pub fn update_round_merkle_root(
+        deps: DepsMut,
+        info: MessageInfo,
+        round_id: u64,
+        merkle_root: Option<HexBinary>,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        if matches!(&merkle_root, Some(root) if root.len() != 32) {
+            return Err(ContractError::InvalidRound {
+                reason: "merkle_root must be 32 bytes".to_string(),
+            });
+        }
+        let mut round = load_round(deps.as_ref(), round_id)?;
+        round.merkle_root = merkle_root;
+        ROUNDS.save(deps.storage, round_id, &round)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "update_round_merkle_root"),
+            ("round_id", round_id.to_string().as_str()),
+            (
+                "merkle_root",
+                round
+                    .merkle_root
+                    .map(|root| root.to_hex())
+                    .unwrap_or_default()
+                    .as_str(),
+            ),
+        ]))
+    }
+
+    pub fn update_sale_caps(
+        deps: DepsMut,
+        info: MessageInfo,
+        sale_caps: SaleCaps,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        validate_sale_caps(&sale_caps)?;
+        SALE_CAPS.save(deps.storage, &sale_caps)?;
+        Ok(Response::new().add_attribute("action", "update_sale_caps"))
+    }
+
+    fn ensure_within_caps(
+        deps: Deps,
+        purchaser: &Purchaser,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        let caps = SALE_CAPS.load(deps.storage)?;
+        if let Some(min_purchase) = caps.min_purchase {
+            if amount < min_purchase {
+                return Err(ContractError::BelowMinimumPurchase {
+                    amount,
+                    min_purchase,
+                });
+            }
+        }
+        ensure_below_caps(deps, purchaser, amount)
+    }
+
+    /// Checks that adding `amount` keeps the purchaser and the sale under their caps.
+    fn ensure_below_caps(
+        deps: Deps,
+        purchaser: &Purchaser,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        let caps = SALE_CAPS.load(deps.storage)?;
+        if let Some(max_per_purchaser) = caps.max_per_purchaser {
+            let purchased = PURCHASE_LIST
+                .may_load(deps.storage, purchaser.clone())?
+                .unwrap_or_default();
+            if purchased + amount > max_per_purchaser {
+                return Err(ContractError::ExceedsPurchaserCap {
+                    purchaser: purchaser.to_string(),
+                    max_per_purchaser,
+                });
+            }
+        }
+        if let Some(hard_cap) = caps.hard_cap {
+            let total = TOTAL_PURCHASED.may_load(deps.storage)?.unwrap_or_default();
+            if total + amount > hard_cap {
+                return Err(ContractError::ExceedsHardCap { hard_cap });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn update_token_metadata(
+        deps: DepsMut,
+        info: MessageInfo,
+        token_metadata: TokenMetadata,
+    ) -> Result<Response<PalomaMsg>, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_finalized(&state)?;
+        validate_token_metadata(&token_metadata)?;
+        TOKEN_METADATA.save(deps.storage, &token_metadata)?;
+        Ok(Response::new().add_attributes(vec![
+            ("action", "update_token_metadata"),
+            ("subdenom", token_metadata.subdenom.as_str()),
+        ]))
+    }
+
+    fn set_erc20_to_denom_msg(
+        denom: &str,
+        chain_id: String,
+        erc20_address: String,
+    ) -> CosmosMsg<PalomaMsg> {
+        CosmosMsg::Custom(PalomaMsg::SkywayMsg {
+            send_tx: None,
+            set_erc20_to_denom: Some(SetErc20ToDenom {
+                erc20_address,
+                token_denom: denom.to_string(),
+                chain_reference_id: chain_id,
+            }),
+        })
+    }
+
+    fn denom_metadata(token: &TokenMetadata, denom: &str) -> Metadata {
+        Metadata {
+            description: token.description.clone(),
+            denom_units: vec![
+                DenomUnit {
+                    denom: denom.to_string(),
+                    exponent: 0,
+                    aliases: vec![],
+                },
+                DenomUnit {
+                    denom: token.display.clone(),
+                    exponent: token.decimals,
+                    aliases: vec![],
+                },
+            ],
+            base: denom.to_string(),
+            display: token.display.clone(),
+            name: token.name.clone(),
+            symbol: token.symbol.clone(),
+        }
+    }
 
+    fn load_chain_settings(deps: Deps, chain_id: &str) -> Result<ChainSettings, ContractError> {
+        CHAIN_SETTINGS
+            .may_load(deps.storage, chain_id.to_string())?
+            .ok_or_else(|| ContractError::UnknownChain {
+                chain_id: chain_id.to_string(),
+            })
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps, _env: Env, _msg: QueryMsg) -> StdResult<Binary> {
-    unimplemented!()
-    // match msg {
-    //     QueryMsg::GetCount {} => to_json_binary(&query::count(deps)?),
-    // }
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_json_binary(&STATE.load(deps.storage)?),
+        QueryMsg::PauseStatus {} => {
+            to_json_binary(&PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::PendingOwner {} => to_json_binary(&PENDING_OWNER.may_load(deps.storage)?),
+        QueryMsg::Proposal { proposal_id } => {
+            to_json_binary(&PROPOSALS.load(deps.storage, proposal_id)?)
+        }
+        QueryMsg::OpenProposals { start_after, limit } => {
+            to_json_binary(&query::open_proposals(deps, env, start_after, limit)?)
+        }
+        QueryMsg::QueuedChanges { start_after, limit } => {
+            to_json_binary(&query::queued_changes(deps, start_after, limit)?)
+        }
+        QueryMsg::Round { round_id } => to_json_binary(&ROUNDS.load(deps.storage, round_id)?),
+        QueryMsg::Rounds { start_after, limit } => {
+            to_json_binary(&query::rounds(deps, start_after, limit)?)
+        }
+        QueryMsg::RoundPurchase {
+            round_id,
+            purchaser,
+        } => to_json_binary(&query::round_purchase(deps, round_id, purchaser)?),
+        QueryMsg::SaleCaps {} => to_json_binary(&SALE_CAPS.load(deps.storage)?),
+        QueryMsg::RemainingCapacity { purchaser } => {
+            to_json_binary(&query::remaining_capacity(deps, purchaser)?)
+        }
+        QueryMsg::Purchase { purchaser } => to_json_binary(&query::purchase(deps, purchaser)?),
+        QueryMsg::AllPurchases { start_after, limit } => {
+            to_json_binary(&query::all_purchases(deps, start_after, limit)?)
+        }
+        QueryMsg::TotalPurchased {} => to_json_binary(&query::total_purchased(deps)?),
+        QueryMsg::PurchaseRecord { record_id } => {
+            to_json_binary(&PURCHASE_RECORDS.load(deps.storage, record_id)?)
+        }
+        QueryMsg::PurchasesByPurchaser {
+            purchaser,
+            start_after,
+            limit,
+        } => to_json_binary(&query::purchases_by_purchaser(
+            deps,
+            purchaser,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::PurchasesByChain {
+            chain_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query::purchases_by_chain(
+            deps,
+            chain_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::PurchaseByOrigin {
+            chain_id,
+            tx_hash,
+            log_index,
+        } => to_json_binary(&purchase_by_origin(deps, &chain_id, &tx_hash, log_index)?),
+        QueryMsg::PurchaseAdjustments {
+            purchaser,
+            start_after,
+            limit,
+        } => to_json_binary(&query::purchase_adjustments(
+            deps,
+            purchaser,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::TokenMetadata {} => to_json_binary(&TOKEN_METADATA.load(deps.storage)?),
+        QueryMsg::DistributionStatus {} => to_json_binary(&DISTRIBUTION.load(deps.storage)?),
+        QueryMsg::Erc20Address { chain_id } => {
+            to_json_binary(&ERC20_ADDRESSES.may_load(deps.storage, chain_id)?)
+        }
+        QueryMsg::ChainSettings { chain_id } => {
+            to_json_binary(&CHAIN_SETTINGS.load(deps.storage, chain_id)?)
+        }
+    }
 }
 
 pub mod query {
-    // use super::*;
+    use cosmwasm_std::{Order, StdError};
+    use cw_storage_plus::Bound;
+
+    use crate::msg::{
+        AllPurchasesResponse, ProposalsResponse, PurchaseAdjustmentsResponse,
+        PurchaseRecordsResponse, PurchaseResponse, QueuedChangesResponse,
+        RemainingCapacityResponse, RoundsResponse, TotalPurchasedResponse,
+    };
+    use crate::state::{
+        ProposalStatus, ADJUSTMENTS, PURCHASE_LIST, QUEUED_CHANGES, ROUND_PURCHASES,
+    };
+
+    use super::*;
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    fn purchaser_key(deps: Deps, purchaser: &str) -> StdResult<Purchaser> {
+        parse_purchaser(deps.api, purchaser).map_err(|e| StdError::generic_err(e.to_string()))
+    }
+
+    pub fn purchase(deps: Deps, purchaser: String) -> StdResult<PurchaseResponse> {
+        let purchaser = purchaser_key(deps, &purchaser)?;
+        let amount = PURCHASE_LIST
+            .may_load(deps.storage, purchaser.clone())?
+            .unwrap_or_default();
+        Ok(PurchaseResponse {
+            purchaser: purchaser.to_string(),
+            amount,
+        })
+    }
+
+    pub fn round_purchase(
+        deps: Deps,
+        round_id: u64,
+        purchaser: String,
+    ) -> StdResult<PurchaseResponse> {
+        let purchaser = purchaser_key(deps, &purchaser)?;
+        let amount = ROUND_PURCHASES
+            .may_load(deps.storage, (round_id, purchaser.clone()))?
+            .unwrap_or_default();
+        Ok(PurchaseResponse {
+            purchaser: purchaser.to_string(),
+            amount,
+        })
+    }
+
+    pub fn rounds(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<RoundsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let rounds = ROUNDS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, round)| round))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(RoundsResponse { rounds })
+    }
+
+    pub fn remaining_capacity(
+        deps: Deps,
+        purchaser: Option<String>,
+    ) -> StdResult<RemainingCapacityResponse> {
+        let caps = SALE_CAPS.load(deps.storage)?;
+        let total_purchased = TOTAL_PURCHASED.may_load(deps.storage)?.unwrap_or_default();
+        let purchaser_remaining = match (purchaser, caps.max_per_purchaser) {
+            (Some(purchaser), Some(max_per_purchaser)) => {
+                let purchased = PURCHASE_LIST
+                    .may_load(deps.storage, purchaser_key(deps, &purchaser)?)?
+                    .unwrap_or_default();
+                Some(max_per_purchaser.saturating_sub(purchased))
+            }
+            _ => None,
+        };
+        Ok(RemainingCapacityResponse {
+            total_purchased,
+            soft_cap_reached: !matches!(caps.soft_cap, Some(soft_cap) if total_purchased < soft_cap),
+            hard_cap_remaining: caps
+                .hard_cap
+                .map(|hard_cap| hard_cap.saturating_sub(total_purchased)),
+            purchaser_remaining,
+        })
+    }
+
+    pub fn all_purchases(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllPurchasesResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after
+            .map(|purchaser| purchaser_key(deps, &purchaser))
+            .transpose()?
+            .map(Bound::exclusive);
+        let purchases = PURCHASE_LIST
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(purchaser, amount)| PurchaseResponse {
+                    purchaser: purchaser.to_string(),
+                    amount,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(AllPurchasesResponse { purchases })
+    }
+
+    pub fn open_proposals(
+        deps: Deps,
+        env: Env,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ProposalsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let proposals = PROPOSALS
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|item| match item {
+                Ok((_, proposal)) => {
+                    proposal.status == ProposalStatus::Open && env.block.time < proposal.expires_at
+                }
+                Err(_) => true,
+            })
+            .take(limit)
+            .map(|item| item.map(|(_, proposal)| proposal))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(ProposalsResponse { proposals })
+    }
+
+    pub fn queued_changes(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<QueuedChangesResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let changes = QUEUED_CHANGES
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, change)| change))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(QueuedChangesResponse { changes })
+    }
+
+    pub fn purchases_by_purchaser(
+        deps: Deps,
+        purchaser: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<PurchaseRecordsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let records = PURCHASE_RECORDS
+            .idx
+            .purchaser
+            .prefix(purchaser_key(deps, &purchaser)?)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, record)| record))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(PurchaseRecordsResponse { records })
+    }
+
+    pub fn purchases_by_chain(
+        deps: Deps,
+        chain_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<PurchaseRecordsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let records = PURCHASE_RECORDS
+            .idx
+            .chain
+            .prefix(chain_id)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, record)| record))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(PurchaseRecordsResponse { records })
+    }
+
+    pub fn purchase_adjustments(
+        deps: Deps,
+        purchaser: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<PurchaseAdjustmentsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let adjustments = ADJUSTMENTS
+            .prefix(purchaser_key(deps, &purchaser)?)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, adjustment)| adjustment))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(PurchaseAdjustmentsResponse { adjustments })
+    }
+
+    pub fn total_purchased(deps: Deps) -> StdResult<TotalPurchasedResponse> {
+        let total = TOTAL_PURCHASED.may_load(deps.storage)?.unwrap_or_default();
+        Ok(TotalPurchasedResponse { total })
+    }
+}
+
+/// Migration run on contracts stored at a version below the one it is registered with.
+/// Returns attributes to add to the migrate response.
+type Migration = fn(DepsMut, &Env, &MigrateMsg) -> Result<Vec<Attribute>, ContractError>;
 
-    // pub fn count(deps: Deps) -> StdResult<GetCountResponse> {
-    //     let state = STATE.load(deps.storage)?;
-    //     Ok(GetCountResponse { count: state.count })
-    // }
+const MIGRATIONS: &[(&str, Migration)] = &[("0.2.0", migrate::legacy_state)];
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigration {
+            reason: format!("cannot migrate from {}", stored.contract),
+        });
+    }
+    let stored_version = parse_version(&stored.version)?;
+    if stored_version > parse_version(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    let mut response = Response::new().add_attributes(vec![
+        ("action", "migrate"),
+        ("from_version", stored.version.as_str()),
+        ("to_version", CONTRACT_VERSION),
+    ]);
+    for (version, migration) in MIGRATIONS {
+        if stored_version < parse_version(version)? {
+            let attributes = migration(deps.branch(), &env, &msg)?;
+            response = response
+                .add_attribute("migration", *version)
+                .add_attributes(attributes);
+        }
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(response)
+}
+
+fn parse_version(version: &str) -> Result<semver::Version, ContractError> {
+    semver::Version::parse(version).map_err(|e| ContractError::InvalidMigration {
+        reason: format!("invalid version {}: {}", version, e),
+    })
+}
+
+pub mod migrate {
+    use std::collections::BTreeMap;
+
+    use cosmwasm_std::Order;
+
+    use super::*;
+    use crate::state::{
+        ALLOCATIONS, CHAIN_PURCHASED, LEGACY_STATE, PURCHASE_CHAINS, PURCHASE_LIST,
+    };
+
+    /// Expands the original `{owners, finished}` state into the current `State`
+    /// and backfills the storage it predates. No-op if already migrated.
+    ///
+    /// Legacy keys that are not valid addresses are dropped and reported as
+    /// `skipped_purchaser`/`skipped_amount` attributes so owners can re-credit
+    /// them with `AdjustPurchase`.
+    pub fn legacy_state(
+        deps: DepsMut,
+        env: &Env,
+        msg: &MigrateMsg,
+    ) -> Result<Vec<Attribute>, ContractError> {
+        if STATE.load(deps.storage).is_ok() {
+            return Ok(vec![]);
+        }
+        let legacy = LEGACY_STATE.load(deps.storage)?;
+        let upgrade = msg
+            .legacy
+            .as_ref()
+            .ok_or_else(|| ContractError::InvalidMigration {
+                reason: "legacy state requires upgrade settings".to_string(),
+            })?;
+
+        let state = State {
+            owners: legacy.owners,
+            finished: legacy.finished,
+            cancelled: false,
+            threshold: upgrade.threshold.unwrap_or(1),
+            timelock_delay: upgrade.timelock_delay,
+            start_time: upgrade.start_time,
+            end_time: upgrade.end_time,
+        };
+        ensure_valid_threshold(&state)?;
+        if state.start_time >= state.end_time || state.end_time <= env.block.time {
+            return Err(ContractError::InvalidSaleWindow {
+                reason: "end_time must be after start_time and in the future".to_string(),
+            });
+        }
+        validate_token_metadata(&upgrade.token_metadata)?;
+        let sale_caps = upgrade.sale_caps.clone().unwrap_or_default();
+        validate_sale_caps(&sale_caps)?;
+
+        validate_chain_settings(&upgrade.chain_settings)?;
+        CHAIN_SETTINGS.save(
+            deps.storage,
+            upgrade.chain_id.clone(),
+            &upgrade.chain_settings,
+        )?;
+
+        // Legacy keys are the raw purchaser strings, so the same buyer may appear
+        // under several spellings; re-key them canonically and merge.
+        let purchases = PURCHASE_LIST
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut merged: BTreeMap<String, (Purchaser, Uint128)> = BTreeMap::new();
+        let mut skipped = vec![];
+        for (key, amount) in purchases {
+            PURCHASE_LIST.remove(deps.storage, key.clone());
+            let Ok(purchaser) = parse_purchaser(deps.api, key.as_str()) else {
+                skipped.push(Attribute::new("skipped_purchaser", key.as_str()));
+                skipped.push(Attribute::new("skipped_amount", amount.to_string()));
+                continue;
+            };
+            merged
+                .entry(purchaser.to_string())
+                .or_insert((purchaser, Uint128::zero()))
+                .1 += amount;
+        }
+
+        // Purchases recorded before allocations existed were all at the flat terms,
+        // and before per-chain tracking all came from `upgrade.chain_id`.
+        let mut total = Uint128::zero();
+        for (purchaser, amount) in merged.into_values() {
+            PURCHASE_LIST.save(deps.storage, purchaser.clone(), &amount)?;
+            ALLOCATIONS.save(deps.storage, purchaser.clone(), &amount)?;
+            PURCHASE_CHAINS.save(deps.storage, (purchaser, upgrade.chain_id.clone()), &amount)?;
+            total += amount;
+        }
+        CHAIN_PURCHASED.save(deps.storage, upgrade.chain_id.clone(), &total)?;
+
+        STATE.save(deps.storage, &state)?;
+        TOKEN_METADATA.save(deps.storage, &upgrade.token_metadata)?;
+        SALE_CAPS.save(deps.storage, &sale_caps)?;
+        TOTAL_PURCHASED.save(deps.storage, &total)?;
+        TOTAL_ALLOCATION.save(deps.storage, &total)?;
+        Ok(skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env, MockApi};
+    use cosmwasm_std::Uint256;
+    use cw_storage_plus::Map;
+
+    use super::*;
+    use crate::msg::LegacyUpgrade;
+    use crate::state::{
+        LegacyState, ALLOCATIONS, CHAIN_PURCHASED, LEGACY_STATE, PURCHASE_CHAINS, PURCHASE_LIST,
+    };
+
+    // Examples from the EIP-55 specification.
+    const EIP55_VECTORS: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn eip55_checksum_matches_spec_vectors() {
+        for address in EIP55_VECTORS {
+            assert_eq!(eip55_checksum(&address[2..]), address);
+            assert_eq!(eip55_checksum(&address[2..].to_ascii_uppercase()), address);
+        }
+    }
+
+    #[test]
+    fn parse_purchaser_checksums_single_case_evm_addresses() {
+        let api = MockApi::default();
+        for address in EIP55_VECTORS {
+            let expected = Purchaser::Evm(address.to_string());
+            let lower = format!("0x{}", address[2..].to_ascii_lowercase());
+            let upper = format!("0x{}", address[2..].to_ascii_uppercase());
+            assert_eq!(parse_purchaser(&api, address).unwrap(), expected);
+            assert_eq!(parse_purchaser(&api, &lower).unwrap(), expected);
+            assert_eq!(parse_purchaser(&api, &upper).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn parse_purchaser_rejects_bad_checksums_and_malformed_addresses() {
+        let api = MockApi::default();
+        for purchaser in [
+            // Spec vectors with the case of a single letter flipped.
+            "0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xFB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6Fb",
+            // Wrong length and non-hex digits.
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAez",
+            "not-an-address",
+        ] {
+            assert!(matches!(
+                parse_purchaser(&api, purchaser),
+                Err(ContractError::InvalidPurchaser { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_purchaser_normalizes_bech32_case() {
+        let api = MockApi::default();
+        let buyer = api.addr_make("buyer");
+        let upper = buyer.as_str().to_uppercase();
+        assert_eq!(
+            parse_purchaser(&api, &upper).unwrap(),
+            Purchaser::Cosmos(buyer)
+        );
+    }
+
+    fn legacy_upgrade(env: &Env) -> LegacyUpgrade {
+        LegacyUpgrade {
+            chain_id: "eth-main".to_string(),
+            chain_settings: ChainSettings {
+                main_job_id: "job".to_string(),
+                compass: EIP55_VECTORS[0].to_string(),
+                refund_wallet: EIP55_VECTORS[1].to_string(),
+                gas_fee: Uint256::zero(),
+                service_fee_collector: EIP55_VECTORS[2].to_string(),
+                service_fee: Uint256::zero(),
+                chain_type: "evm".to_string(),
+                sale_contract: None,
+                refund_denom: None,
+            },
+            threshold: None,
+            timelock_delay: 0,
+            token_metadata: TokenMetadata {
+                subdenom: "gpu".to_string(),
+                name: "GPU".to_string(),
+                symbol: "GPU".to_string(),
+                description: "GPU DAO token".to_string(),
+                display: "gpu".to_string(),
+                decimals: 6,
+            },
+            sale_caps: None,
+            start_time: env.block.time,
+            end_time: env.block.time.plus_seconds(1000),
+        }
+    }
+
+    #[test]
+    fn migrate_upgrades_legacy_state_and_rekeys_purchasers() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let owner = deps.api.addr_make("owner");
+        let buyer = deps.api.addr_make("buyer");
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+        LEGACY_STATE
+            .save(
+                &mut deps.storage,
+                &LegacyState {
+                    owners: vec![owner.clone()],
+                    finished: false,
+                },
+            )
+            .unwrap();
+        // Legacy keys are raw strings: three spellings of one EVM buyer, two of
+        // one bech32 buyer, and one that is no address at all.
+        let evm = EIP55_VECTORS[3];
+        let raw_list: Map<&str, Uint128> = Map::new("purchase_list");
+        for (key, amount) in [
+            (evm.to_string(), 1u128),
+            (format!("0x{}", evm[2..].to_ascii_lowercase()), 2),
+            (format!("0x{}", evm[2..].to_ascii_uppercase()), 4),
+            (buyer.to_string(), 10),
+            (buyer.as_str().to_uppercase(), 20),
+            ("not an address".to_string(), 100),
+        ] {
+            raw_list
+                .save(&mut deps.storage, &key, &Uint128::new(amount))
+                .unwrap();
+        }
+
+        let upgrade = legacy_upgrade(&env);
+        let response = migrate(
+            deps.as_mut(),
+            env,
+            MigrateMsg {
+                legacy: Some(upgrade.clone()),
+            },
+        )
+        .unwrap();
+
+        let evm = Purchaser::Evm(evm.to_string());
+        let cosmos = Purchaser::Cosmos(buyer);
+        for (purchaser, amount) in [(evm, 7u128), (cosmos, 30)] {
+            let amount = Uint128::new(amount);
+            let storage = &deps.storage;
+            assert_eq!(
+                PURCHASE_LIST.load(storage, purchaser.clone()).unwrap(),
+                amount
+            );
+            assert_eq!(
+                ALLOCATIONS.load(storage, purchaser.clone()).unwrap(),
+                amount
+            );
+            assert_eq!(
+                PURCHASE_CHAINS
+                    .load(storage, (purchaser, upgrade.chain_id.clone()))
+                    .unwrap(),
+                amount
+            );
+        }
+        assert_eq!(
+            PURCHASE_LIST
+                .keys(&deps.storage, None, None, cosmwasm_std::Order::Ascending)
+                .count(),
+            2
+        );
+        assert_eq!(
+            TOTAL_PURCHASED.load(&deps.storage).unwrap(),
+            Uint128::new(37)
+        );
+        assert_eq!(
+            CHAIN_PURCHASED
+                .load(&deps.storage, upgrade.chain_id.clone())
+                .unwrap(),
+            Uint128::new(37)
+        );
+        assert_eq!(
+            CHAIN_SETTINGS
+                .load(&deps.storage, upgrade.chain_id.clone())
+                .unwrap(),
+            upgrade.chain_settings
+        );
+        let state = STATE.load(&deps.storage).unwrap();
+        assert_eq!(state.owners, vec![owner]);
+        assert_eq!(state.threshold, 1);
+
+        let attribute = |key: &str| {
+            response
+                .attributes
+                .iter()
+                .find(|attribute| attribute.key == key)
+                .map(|attribute| attribute.value.as_str())
+        };
+        assert_eq!(attribute("migration"), Some("0.2.0"));
+        assert_eq!(attribute("skipped_purchaser"), Some("not an address"));
+        assert_eq!(attribute("skipped_amount"), Some("100"));
+        assert_eq!(
+            get_contract_version(&deps.storage).unwrap().version,
+            CONTRACT_VERSION
+        );
+    }
+
+    #[test]
+    fn migrate_rejects_invalid_legacy_chain_settings() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+        LEGACY_STATE
+            .save(
+                &mut deps.storage,
+                &LegacyState {
+                    owners: vec![deps.api.addr_make("owner")],
+                    finished: false,
+                },
+            )
+            .unwrap();
+        let mut upgrade = legacy_upgrade(&env);
+        upgrade.chain_settings.compass = "not an address".to_string();
+
+        let err = migrate(
+            deps.as_mut(),
+            env,
+            MigrateMsg {
+                legacy: Some(upgrade),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidEvmAddress { .. }));
+        assert!(STATE.load(&deps.storage).is_err());
+    }
+
+    #[test]
+    fn migrate_refuses_downgrades_and_foreign_contracts() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let owner = deps.api.addr_make("owner");
+        let upgrade = legacy_upgrade(&env);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&owner, &[]),
+            InstantiateMsg {
+                owners: vec![owner.to_string()],
+                threshold: None,
+                timelock_delay: 0,
+                token_metadata: upgrade.token_metadata,
+                sale_caps: None,
+                start_time: upgrade.start_time,
+                end_time: upgrade.end_time,
+            },
+        )
+        .unwrap();
+
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "9.9.9").unwrap();
+        let err = migrate(deps.as_mut(), env.clone(), MigrateMsg { legacy: None }).unwrap_err();
+        assert!(matches!(err, ContractError::CannotDowngrade { .. }));
+
+        set_contract_version(&mut deps.storage, "crates.io:other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), env, MigrateMsg { legacy: None }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMigration { .. }));
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Timestamp, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,8 +6,202 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("Paused: {scope}")]
+    Paused { scope: String },
+
+    #[error("Invalid migration: {reason}")]
+    InvalidMigration { reason: String },
+
+    #[error("Cannot migrate from {stored} down to {current}")]
+    CannotDowngrade { stored: String, current: String },
+
     #[error("Unauthorized")]
     Unauthorized {},
-    // Add any other custom errors you like here.
-    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+
+    #[error("{owner} is already an owner")]
+    AlreadyOwner { owner: String },
+
+    #[error("{address} is not an owner")]
+    NotOwner { address: String },
+
+    #[error("Cannot remove the last owner")]
+    LastOwner {},
+
+    #[error("Threshold must be between 1 and the number of owners")]
+    InvalidThreshold {},
+
+    #[error("This message must be approved through a proposal")]
+    ProposalRequired {},
+
+    #[error("This message cannot be proposed")]
+    NotProposable {},
+
+    #[error("Proposal {proposal_id} not found")]
+    ProposalNotFound { proposal_id: u64 },
+
+    #[error("Proposal {proposal_id} is not open")]
+    ProposalNotOpen { proposal_id: u64 },
+
+    #[error("Proposal {proposal_id} has expired")]
+    ProposalExpired { proposal_id: u64 },
+
+    #[error("Already voted on proposal {proposal_id}")]
+    AlreadyVoted { proposal_id: u64 },
+
+    #[error("Proposal {proposal_id} has not reached the threshold")]
+    ThresholdNotReached { proposal_id: u64 },
+
+    #[error("Queued change {change_id} not found")]
+    QueuedChangeNotFound { change_id: u64 },
+
+    #[error("Queued change {change_id} cannot be executed before {eta}")]
+    TimelockNotExpired { change_id: u64, eta: Timestamp },
+
+    #[error("No pending ownership transfer")]
+    NoPendingOwner {},
+
+    #[error("The contract has already been finalized")]
+    AlreadyFinalized {},
+
+    #[error("Amount must be greater than zero")]
+    ZeroAmount {},
+
+    #[error("Purchase of {amount} is below the minimum of {min_purchase}")]
+    BelowMinimumPurchase {
+        amount: Uint128,
+        min_purchase: Uint128,
+    },
+
+    #[error("Purchase would exceed the cap of {max_per_purchaser} for {purchaser}")]
+    ExceedsPurchaserCap {
+        purchaser: String,
+        max_per_purchaser: Uint128,
+    },
+
+    #[error("Purchase would exceed the hard cap of {hard_cap}")]
+    ExceedsHardCap { hard_cap: Uint128 },
+
+    #[error("Soft cap of {soft_cap} has not been reached")]
+    SoftCapNotReached { soft_cap: Uint128 },
+
+    #[error("The sale opens at {start_time}")]
+    SaleNotStarted { start_time: Timestamp },
+
+    #[error("The sale closed at {end_time}")]
+    SaleEnded { end_time: Timestamp },
+
+    #[error("The sale is open until {end_time}")]
+    SaleNotEnded { end_time: Timestamp },
+
+    #[error("Invalid sale window: {reason}")]
+    InvalidSaleWindow { reason: String },
+
+    #[error("Round {round_id} not found")]
+    RoundNotFound { round_id: u64 },
+
+    #[error("Round {round_id} is not open")]
+    RoundNotOpen { round_id: u64 },
+
+    #[error("{purchaser} is not allowlisted for round {round_id}")]
+    NotAllowlisted { round_id: u64, purchaser: String },
+
+    #[error("Purchase would exceed the cap of {cap} for round {round_id}")]
+    ExceedsRoundCap { round_id: u64, cap: Uint128 },
+
+    #[error("Round {round_id} requires an allocation proof")]
+    MissingAllocationProof { round_id: u64 },
+
+    #[error("Invalid allocation proof for {purchaser} in round {round_id}")]
+    InvalidAllocationProof { round_id: u64, purchaser: String },
+
+    #[error("Purchase would exceed the allocation of {max_allocation} for {purchaser}")]
+    ExceedsMaxAllocation {
+        purchaser: String,
+        max_allocation: Uint128,
+    },
+
+    #[error("Invalid round: {reason}")]
+    InvalidRound { reason: String },
+
+    #[error("Invalid sale caps: {reason}")]
+    InvalidSaleCaps { reason: String },
+
+    #[error("ABI encoding failed: {0}")]
+    AbiEncoding(#[from] ethabi::Error),
+
+    #[error("Invalid token metadata: {reason}")]
+    InvalidTokenMetadata { reason: String },
+
+    #[error("The contract has not been finalized")]
+    NotFinalized {},
+
+    #[error("Distribution has already completed")]
+    DistributionCompleted {},
+
+    #[error("No source chain recorded for {purchaser}")]
+    NoPurchaseChains { purchaser: String },
+
+    #[error("Token is not bridged to chain {chain_id}")]
+    TokenNotBridged { chain_id: String },
+
+    #[error("Distribute amount exceeds mint amount")]
+    DistributionExceedsMint {},
+
+    #[error("The sale has been cancelled")]
+    SaleCancelled {},
+
+    #[error("The sale has not been cancelled")]
+    SaleNotCancelled {},
+
+    #[error("{purchaser} has already been refunded")]
+    AlreadyRefunded { purchaser: String },
+
+    #[error("{purchaser} has nothing to refund")]
+    NothingToRefund { purchaser: String },
+
+    #[error("Purchase from {chain_id} tx {tx_hash} log {log_index} was already recorded")]
+    DuplicatePurchase {
+        chain_id: String,
+        tx_hash: String,
+        log_index: u64,
+    },
+
+    #[error("Invalid adjustment: {reason}")]
+    InvalidAdjustment { reason: String },
+
+    #[error("Batch has no entries")]
+    EmptyBatch {},
+
+    #[error("No sale contract registered for {chain_id}")]
+    SaleContractNotSet { chain_id: String },
+
+    #[error("Log from {address} was not emitted by the {chain_id} sale contract")]
+    UnexpectedLogEmitter { chain_id: String, address: String },
+
+    #[error("Invalid purchase log: {reason}")]
+    InvalidPurchaseLog { reason: String },
+
+    #[error("Invalid purchase origin: {reason}")]
+    InvalidPurchaseOrigin { reason: String },
+
+    #[error("Invalid purchaser {purchaser}")]
+    InvalidPurchaser { purchaser: String },
+
+    #[error("No refund denom configured for {chain_id}")]
+    RefundDenomNotSet { chain_id: String },
+
+    #[error("Invalid EVM address {address}")]
+    InvalidEvmAddress { address: String },
+
+    #[error("Chain {chain_id} is already registered")]
+    ChainAlreadyRegistered { chain_id: String },
+
+    #[error("Unknown chain {chain_id}")]
+    UnknownChain { chain_id: String },
+
+    #[error("Chain {chain_id} still has {amount} in recorded purchases")]
+    ChainHasPurchases { chain_id: String, amount: Uint128 },
+
+    #[error("Chain {chain_id} has queued change {change_id}")]
+    ChainHasQueuedChanges { chain_id: String, change_id: u64 },
 }
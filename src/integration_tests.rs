@@ -0,0 +1,1487 @@
+use cosmwasm_std::{
+    coins, to_json_string, Addr, Api, Binary, BlockInfo, CustomMsg, CustomQuery, Decimal, Empty,
+    Event, HexBinary, Int128, Querier, Storage, Uint128, Uint256,
+};
+use cw_multi_test::error::AnyResult;
+use cw_multi_test::{
+    App, AppResponse, BankKeeper, BankSudo, BasicAppBuilder, ContractWrapper, CosmosRouter,
+    Executor, Module, WasmKeeper,
+};
+use serde::de::DeserializeOwned;
+
+use crate::contract::{execute, instantiate, query};
+use crate::msg::{
+    AllocationProof, ExecuteMsg, InstantiateMsg, PalomaMsg, PurchaseEntry, PurchaseOrigin,
+    PurchaseRecordsResponse, PurchaseResponse, QueryMsg, RelayedLog, RemainingCapacityResponse,
+    TotalPurchasedResponse,
+};
+use crate::state::{
+    ChainSettings, Distribution, PauseScope, PurchaseRecord, SaleCaps, State, TokenMetadata,
+};
+use crate::ContractError;
+
+const CHAIN_ID: &str = "eth-main";
+const PAYMENT_TOKEN: &str = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+const REFUND_DENOM: &str = "upusd";
+const SALE_DURATION: u64 = 1_000;
+
+/// Stands in for the Paloma chain: token factory mints are credited through
+/// the bank module, every other message is accepted and echoed as an event.
+struct PalomaModule;
+
+impl Module for PalomaModule {
+    type ExecT = PalomaMsg;
+    type QueryT = Empty;
+    type SudoT = Empty;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        _sender: Addr,
+        msg: PalomaMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        if let PalomaMsg::TokenFactoryMsg {
+            mint_tokens: Some(mint),
+            ..
+        } = &msg
+        {
+            let mint = BankSudo::Mint {
+                to_address: mint.mint_to_address.clone(),
+                amount: coins(mint.amount.u128(), &mint.denom),
+            };
+            return router.sudo(api, storage, block, mint.into());
+        }
+        Ok(AppResponse {
+            events: vec![Event::new("paloma").add_attribute("msg", to_json_string(&msg)?)],
+            data: None,
+        })
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        _storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        _request: Empty,
+    ) -> AnyResult<Binary> {
+        Ok(Binary::default())
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        _msg: Empty,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        Ok(AppResponse::default())
+    }
+}
+
+type PalomaApp = App<
+    BankKeeper,
+    cosmwasm_std::testing::MockApi,
+    cosmwasm_std::testing::MockStorage,
+    PalomaModule,
+    WasmKeeper<PalomaMsg, Empty>,
+>;
+
+struct Suite {
+    app: PalomaApp,
+    contract: Addr,
+    owners: Vec<Addr>,
+    transactions: u8,
+}
+
+impl Suite {
+    fn new(owner_count: usize, threshold: Option<u32>, timelock_delay: u64) -> Self {
+        Self::with_start_delay(owner_count, threshold, timelock_delay, 0)
+    }
+
+    /// Like `new`, with the sale opening `start_delay` seconds from now.
+    fn with_start_delay(
+        owner_count: usize,
+        threshold: Option<u32>,
+        timelock_delay: u64,
+        start_delay: u64,
+    ) -> Self {
+        let mut app = BasicAppBuilder::<PalomaMsg, Empty>::new_custom()
+            .with_custom(PalomaModule)
+            .build(|_, _, _| {});
+        let owners: Vec<Addr> = (0..owner_count)
+            .map(|i| app.api().addr_make(&format!("owner{}", i)))
+            .collect();
+        let code = ContractWrapper::new(
+            execute,
+            |deps, env, info, msg| {
+                instantiate(deps, env, info, msg).map(|response| response.change_custom().unwrap())
+            },
+            query,
+        );
+        let code_id = app.store_code(Box::new(code));
+        let start_time = app.block_info().time.plus_seconds(start_delay);
+        let contract = app
+            .instantiate_contract(
+                code_id,
+                owners[0].clone(),
+                &InstantiateMsg {
+                    owners: owners.iter().map(|x| x.to_string()).collect(),
+                    threshold,
+                    timelock_delay,
+                    token_metadata: TokenMetadata {
+                        subdenom: "gpu".to_string(),
+                        name: "GPU".to_string(),
+                        symbol: "GPU".to_string(),
+                        description: "GPU DAO token".to_string(),
+                        display: "gpu".to_string(),
+                        decimals: 6,
+                    },
+                    sale_caps: None,
+                    start_time,
+                    end_time: start_time.plus_seconds(SALE_DURATION),
+                },
+                &[],
+                "gpu-dao",
+                None,
+            )
+            .unwrap();
+        let mut suite = Suite {
+            app,
+            contract,
+            owners,
+            transactions: 0,
+        };
+        if threshold.unwrap_or(1) == 1 {
+            suite
+                .execute(
+                    0,
+                    ExecuteMsg::RegisterChain {
+                        chain_id: CHAIN_ID.to_string(),
+                        chain_settings: chain_settings("job"),
+                    },
+                )
+                .unwrap();
+        }
+        suite
+    }
+
+    fn execute(&mut self, owner: usize, msg: ExecuteMsg) -> AnyResult<AppResponse> {
+        let sender = self.owners[owner].clone();
+        self.execute_as(&sender, msg)
+    }
+
+    fn execute_as(&mut self, sender: &Addr, msg: ExecuteMsg) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(sender.clone(), self.contract.clone(), &msg, &[])
+    }
+
+    fn purchase(
+        &mut self,
+        purchaser: &str,
+        amount: u128,
+        round_id: Option<u64>,
+        allocation_proof: Option<AllocationProof>,
+    ) -> AnyResult<AppResponse> {
+        // Every purchase gets its own source transaction.
+        self.transactions += 1;
+        let tx_hash = HexBinary::from(vec![self.transactions; 32]);
+        self.execute(
+            0,
+            ExecuteMsg::Purchase {
+                chain_id: CHAIN_ID.to_string(),
+                round_id,
+                purchaser: purchaser.to_string(),
+                amount: Uint128::new(amount),
+                allocation_proof,
+                origin: PurchaseOrigin {
+                    tx_hash,
+                    log_index: 0,
+                    payment_token: PAYMENT_TOKEN.to_string(),
+                },
+            },
+        )
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: QueryMsg) -> T {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.contract.clone(), &msg)
+            .unwrap()
+    }
+
+    fn purchased(&self, purchaser: &str) -> Uint128 {
+        self.query::<PurchaseResponse>(QueryMsg::Purchase {
+            purchaser: purchaser.to_string(),
+        })
+        .amount
+    }
+
+    fn balance(&self, address: &Addr, denom: &str) -> Uint128 {
+        self.app
+            .wrap()
+            .query_balance(address, denom)
+            .unwrap()
+            .amount
+    }
+
+    fn advance(&mut self, seconds: u64) {
+        self.app.update_block(|block| {
+            block.time = block.time.plus_seconds(seconds);
+            block.height += 1;
+        });
+    }
+}
+
+fn chain_settings(main_job_id: &str) -> ChainSettings {
+    ChainSettings {
+        main_job_id: main_job_id.to_string(),
+        compass: "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(),
+        refund_wallet: "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359".to_string(),
+        gas_fee: Uint256::zero(),
+        service_fee_collector: "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB".to_string(),
+        service_fee: Uint256::zero(),
+        chain_type: "evm".to_string(),
+        sale_contract: None,
+        refund_denom: Some(REFUND_DENOM.to_string()),
+    }
+}
+
+fn contract_error(err: cw_multi_test::error::AnyError) -> ContractError {
+    err.downcast::<ContractError>().unwrap()
+}
+
+fn paloma_msgs(response: &AppResponse) -> usize {
+    response
+        .events
+        .iter()
+        .filter(|event| event.ty == "paloma")
+        .count()
+}
+
+#[test]
+fn merkle_round_accepts_only_proven_allocations() {
+    let mut suite = Suite::new(1, None, 0);
+    let now = suite.app.block_info().time;
+    suite
+        .execute(
+            0,
+            ExecuteMsg::CreateRound {
+                name: "seed".to_string(),
+                price: Decimal::one(),
+                cap: None,
+                start_time: now,
+                end_time: now.plus_seconds(SALE_DURATION),
+                allowlist_only: false,
+            },
+        )
+        .unwrap();
+
+    // Tree over sha256(purchaser || max_allocation) leaves of the EIP-55 spec
+    // addresses with allocations 1000..4000, hashing sorted pairs.
+    let root = "b41fcd620c4617f24433301cbc72ec4a4cb1c59116a000123fc92268e775f219";
+    let proof = |max_allocation: u128, siblings: [&str; 2]| AllocationProof {
+        max_allocation: Uint128::new(max_allocation),
+        proof: siblings
+            .iter()
+            .map(|x| HexBinary::from_hex(x).unwrap())
+            .collect(),
+    };
+    let first = [
+        "b65f5e83dfa098e9395a686968a4bc6db7df6347f3d20fe783c4b6c4e88623dc",
+        "4b705e5278f40af4a6054c5d57de35412a71f478d606dab2516d05939bd58d03",
+    ];
+    let third = [
+        "e8fd649a47cc852838499ad3d9830570a294800127d603a309a52fc2c1355485",
+        "3e4bf39e14822a08d367a4eb4193fec5edfaefe041225cae4b89d7e15f721fbe",
+    ];
+    suite
+        .execute(
+            0,
+            ExecuteMsg::UpdateRoundMerkleRoot {
+                round_id: 1,
+                merkle_root: Some(HexBinary::from_hex(root).unwrap()),
+            },
+        )
+        .unwrap();
+    suite
+        .execute(0, ExecuteMsg::OpenRound { round_id: 1 })
+        .unwrap();
+
+    let first_buyer = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let third_buyer = "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB";
+    let err = suite.purchase(first_buyer, 100, Some(1), None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::MissingAllocationProof { round_id: 1 }
+    ));
+    for (purchaser, allocation_proof) in [
+        (first_buyer, proof(2_000, first)),
+        (third_buyer, proof(3_000, first)),
+    ] {
+        let err = suite.purchase(purchaser, 100, Some(1), Some(allocation_proof));
+        assert!(matches!(
+            contract_error(err.unwrap_err()),
+            ContractError::InvalidAllocationProof { round_id: 1, .. }
+        ));
+    }
+
+    // Leaves use the checksummed address, whatever case the purchase came in.
+    suite
+        .purchase(
+            &first_buyer.to_lowercase(),
+            600,
+            Some(1),
+            Some(proof(1_000, first)),
+        )
+        .unwrap();
+    suite
+        .purchase(third_buyer, 3_000, Some(1), Some(proof(3_000, third)))
+        .unwrap();
+    let err = suite.purchase(first_buyer, 401, Some(1), Some(proof(1_000, first)));
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::ExceedsMaxAllocation { .. }
+    ));
+    assert_eq!(suite.purchased(first_buyer), Uint128::new(600));
+    assert_eq!(suite.purchased(third_buyer), Uint128::new(3_000));
+}
+
+#[test]
+fn cancelled_sale_refunds_bech32_and_evm_purchasers() {
+    let mut suite = Suite::new(1, None, 0);
+    let buyer = suite.app.api().addr_make("buyer");
+    let evm_buyer = "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb";
+    suite.purchase(buyer.as_str(), 100, None, None).unwrap();
+    suite.purchase(evm_buyer, 200, None, None).unwrap();
+
+    // Refunds need a cancelled sale.
+    let err = suite.execute_as(&buyer, ExecuteMsg::Refund { purchasers: None });
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::SaleNotCancelled {}
+    ));
+    suite.execute(0, ExecuteMsg::CancelSale {}).unwrap();
+
+    // The bridged payment token sits with the contract on Paloma.
+    suite
+        .app
+        .sudo(
+            BankSudo::Mint {
+                to_address: suite.contract.to_string(),
+                amount: coins(100, REFUND_DENOM),
+            }
+            .into(),
+        )
+        .unwrap();
+    suite
+        .execute_as(&buyer, ExecuteMsg::Refund { purchasers: None })
+        .unwrap();
+    assert_eq!(suite.balance(&buyer, REFUND_DENOM), Uint128::new(100));
+    assert_eq!(suite.purchased(buyer.as_str()), Uint128::zero());
+
+    let response = suite
+        .execute(
+            0,
+            ExecuteMsg::Refund {
+                purchasers: Some(vec![evm_buyer.to_lowercase()]),
+            },
+        )
+        .unwrap();
+    assert_eq!(paloma_msgs(&response), 1);
+    assert_eq!(suite.purchased(evm_buyer), Uint128::zero());
+
+    let err = suite.execute_as(&buyer, ExecuteMsg::Refund { purchasers: None });
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::AlreadyRefunded { .. }
+    ));
+
+    // Re-running an owner batch skips whoever has nothing left to refund.
+    let stranger = suite.app.api().addr_make("stranger");
+    let response = suite
+        .execute(
+            0,
+            ExecuteMsg::Refund {
+                purchasers: Some(vec![
+                    buyer.to_string(),
+                    evm_buyer.to_string(),
+                    stranger.to_string(),
+                ]),
+            },
+        )
+        .unwrap();
+    assert_eq!(paloma_msgs(&response), 0);
+    let wasm = response.events.iter().find(|e| e.ty == "wasm").unwrap();
+    let skipped = |key: &str| -> Vec<String> {
+        wasm.attributes
+            .iter()
+            .filter(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+            .collect()
+    };
+    assert_eq!(
+        skipped("already_refunded"),
+        vec![buyer.to_string(), evm_buyer.to_string()]
+    );
+    assert_eq!(skipped("nothing_to_refund"), vec![stranger.to_string()]);
+}
+
+#[test]
+fn chains_are_validated_and_removed_only_when_unused() {
+    let mut suite = Suite::new(1, None, 100);
+    let mut settings = chain_settings("job");
+    settings.compass = "compass".to_string();
+    let err = suite.execute(
+        0,
+        ExecuteMsg::RegisterChain {
+            chain_id: "bsc-main".to_string(),
+            chain_settings: settings,
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::InvalidEvmAddress { .. }
+    ));
+
+    let remove_chain = ExecuteMsg::RemoveChain {
+        chain_id: CHAIN_ID.to_string(),
+    };
+    let buyer = suite.app.api().addr_make("buyer");
+    suite.purchase(buyer.as_str(), 100, None, None).unwrap();
+    let err = suite.execute(0, remove_chain.clone());
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::ChainHasPurchases { .. }
+    ));
+    suite
+        .execute(
+            0,
+            ExecuteMsg::AdjustPurchase {
+                chain_id: CHAIN_ID.to_string(),
+                round_id: None,
+                purchaser: buyer.to_string(),
+                delta: Int128::new(-100),
+                reason: "reorged out".to_string(),
+            },
+        )
+        .unwrap();
+
+    suite
+        .execute(
+            0,
+            ExecuteMsg::UpdateGasFee {
+                chain_id: CHAIN_ID.to_string(),
+                new_gas_fee: Uint256::one(),
+            },
+        )
+        .unwrap();
+    let err = suite.execute(0, remove_chain.clone());
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::ChainHasQueuedChanges { change_id: 1, .. }
+    ));
+    suite
+        .execute(0, ExecuteMsg::CancelQueuedChange { change_id: 1 })
+        .unwrap();
+
+    suite
+        .execute(
+            0,
+            ExecuteMsg::BridgeToken {
+                chain_id: CHAIN_ID.to_string(),
+                erc20_address: PAYMENT_TOKEN.to_string(),
+            },
+        )
+        .unwrap();
+    suite.execute(0, remove_chain).unwrap();
+    let erc20: Option<String> = suite.query(QueryMsg::Erc20Address {
+        chain_id: CHAIN_ID.to_string(),
+    });
+    assert_eq!(erc20, None);
+}
+
+#[test]
+fn governance_messages_need_threshold_approvals() {
+    let mut suite = Suite::new(3, Some(2), 0);
+    let err = suite.execute(0, ExecuteMsg::CancelSale {});
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::ProposalRequired {}
+    ));
+
+    suite
+        .execute(
+            0,
+            ExecuteMsg::Propose {
+                msg: Box::new(ExecuteMsg::CancelSale {}),
+                expires_in: None,
+            },
+        )
+        .unwrap();
+    let err = suite.execute(0, ExecuteMsg::ExecuteProposal { proposal_id: 1 });
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::ThresholdNotReached { proposal_id: 1 }
+    ));
+    let err = suite.execute(0, ExecuteMsg::Vote { proposal_id: 1 });
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::AlreadyVoted { proposal_id: 1 }
+    ));
+    suite
+        .execute(1, ExecuteMsg::Vote { proposal_id: 1 })
+        .unwrap();
+    suite
+        .execute(0, ExecuteMsg::ExecuteProposal { proposal_id: 1 })
+        .unwrap();
+    let state: State = suite.query(QueryMsg::Config {});
+    assert!(state.cancelled);
+}
+
+#[test]
+fn removed_owners_cannot_cancel_their_proposals() {
+    let mut suite = Suite::new(3, Some(2), 0);
+    let propose = |msg: ExecuteMsg| ExecuteMsg::Propose {
+        msg: Box::new(msg),
+        expires_in: None,
+    };
+    suite
+        .execute(2, propose(ExecuteMsg::UpdateThreshold { threshold: 1 }))
+        .unwrap();
+    suite
+        .execute(
+            0,
+            propose(ExecuteMsg::RemoveOwner {
+                owner: suite.owners[2].to_string(),
+            }),
+        )
+        .unwrap();
+    suite
+        .execute(1, ExecuteMsg::Vote { proposal_id: 2 })
+        .unwrap();
+    suite
+        .execute(1, ExecuteMsg::ExecuteProposal { proposal_id: 2 })
+        .unwrap();
+
+    let err = suite.execute(2, ExecuteMsg::CancelProposal { proposal_id: 1 });
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::Unauthorized {}
+    ));
+    suite
+        .execute(0, ExecuteMsg::Vote { proposal_id: 1 })
+        .unwrap();
+    suite
+        .execute(1, ExecuteMsg::Vote { proposal_id: 1 })
+        .unwrap();
+    suite
+        .execute(0, ExecuteMsg::ExecuteProposal { proposal_id: 1 })
+        .unwrap();
+}
+
+#[test]
+fn proposed_owner_replaces_the_named_owner_not_the_executor() {
+    let mut suite = Suite::new(3, Some(2), 0);
+    let newcomer = suite.app.api().addr_make("newcomer");
+    suite
+        .execute(
+            0,
+            ExecuteMsg::Propose {
+                msg: Box::new(ExecuteMsg::ProposeOwner {
+                    new_owner: newcomer.to_string(),
+                    replacing: Some(suite.owners[2].to_string()),
+                }),
+                expires_in: None,
+            },
+        )
+        .unwrap();
+    suite
+        .execute(1, ExecuteMsg::Vote { proposal_id: 1 })
+        .unwrap();
+    suite
+        .execute(1, ExecuteMsg::ExecuteProposal { proposal_id: 1 })
+        .unwrap();
+    suite
+        .execute_as(&newcomer, ExecuteMsg::AcceptOwnership {})
+        .unwrap();
+
+    let state: State = suite.query(QueryMsg::Config {});
+    assert_eq!(
+        state.owners,
+        vec![
+            suite.owners[0].clone(),
+            suite.owners[1].clone(),
+            newcomer.clone()
+        ]
+    );
+}
+
+#[test]
+fn timelocked_changes_wait_out_the_current_delay() {
+    let mut suite = Suite::new(1, None, 100);
+
+    // Lowering the delay is itself queued behind the current delay.
+    suite
+        .execute(0, ExecuteMsg::UpdateTimelockDelay { delay: 0 })
+        .unwrap();
+    suite
+        .execute(
+            0,
+            ExecuteMsg::UpdateChain {
+                chain_id: CHAIN_ID.to_string(),
+                chain_settings: chain_settings("new-job"),
+            },
+        )
+        .unwrap();
+    let settings: ChainSettings = suite.query(QueryMsg::ChainSettings {
+        chain_id: CHAIN_ID.to_string(),
+    });
+    assert_eq!(settings.main_job_id, "job");
+    for change_id in [1, 2] {
+        let err = suite.execute(0, ExecuteMsg::ExecuteQueuedChange { change_id });
+        assert!(matches!(
+            contract_error(err.unwrap_err()),
+            ContractError::TimelockNotExpired { .. }
+        ));
+    }
+
+    suite.advance(100);
+    suite
+        .execute(0, ExecuteMsg::ExecuteQueuedChange { change_id: 1 })
+        .unwrap();
+    suite
+        .execute(0, ExecuteMsg::ExecuteQueuedChange { change_id: 2 })
+        .unwrap();
+    let state: State = suite.query(QueryMsg::Config {});
+    assert_eq!(state.timelock_delay, 0);
+    let settings: ChainSettings = suite.query(QueryMsg::ChainSettings {
+        chain_id: CHAIN_ID.to_string(),
+    });
+    assert_eq!(settings.main_job_id, "new-job");
+}
+
+#[test]
+fn distribution_pays_shares_and_sends_the_rest_to_the_treasury() {
+    let mut suite = Suite::new(1, None, 0);
+    let alice = suite.app.api().addr_make("alice");
+    let bob = suite.app.api().addr_make("bob");
+    let treasury = suite.app.api().addr_make("treasury");
+    suite.purchase(alice.as_str(), 100, None, None).unwrap();
+    suite.purchase(bob.as_str(), 200, None, None).unwrap();
+
+    let finalize = ExecuteMsg::Finalize {
+        mint_amount: Uint128::new(1_000),
+        distribute_amount: Uint128::new(100),
+        pusd_amount: Uint128::new(300),
+        treasury: treasury.to_string(),
+    };
+    let err = suite.execute(0, finalize.clone());
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::SaleNotEnded { .. }
+    ));
+    suite.advance(SALE_DURATION);
+    suite.execute(0, finalize).unwrap();
+
+    let denom = format!("factory/{}/gpu", suite.contract);
+    assert_eq!(suite.balance(&suite.contract, &denom), Uint128::new(100));
+    assert_eq!(suite.balance(&treasury, &denom), Uint128::new(900));
+
+    // Anyone may crank the distribution, one page at a time.
+    let cranker = suite.app.api().addr_make("cranker");
+    suite
+        .execute_as(&cranker, ExecuteMsg::ProcessDistribution { limit: Some(1) })
+        .unwrap();
+    suite
+        .execute_as(&cranker, ExecuteMsg::ProcessDistribution { limit: Some(1) })
+        .unwrap();
+    suite
+        .execute_as(&cranker, ExecuteMsg::ProcessDistribution { limit: Some(1) })
+        .unwrap();
+    let status: Distribution = suite.query(QueryMsg::DistributionStatus {});
+    assert!(status.completed);
+    assert_eq!(status.distributed, Uint128::new(99));
+
+    // 100 split 1:2 rounds down to 33 and 66; the dust goes to the treasury.
+    assert_eq!(suite.balance(&alice, &denom), Uint128::new(33));
+    assert_eq!(suite.balance(&bob, &denom), Uint128::new(66));
+    assert_eq!(suite.balance(&treasury, &denom), Uint128::new(901));
+    assert_eq!(suite.balance(&suite.contract, &denom), Uint128::zero());
+    let err = suite.execute_as(&cranker, ExecuteMsg::ProcessDistribution { limit: None });
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::DistributionCompleted {}
+    ));
+}
+
+#[test]
+fn paused_scopes_block_only_their_messages() {
+    let mut suite = Suite::new(1, None, 0);
+    let buyer = suite.app.api().addr_make("buyer");
+    suite
+        .execute(
+            0,
+            ExecuteMsg::Pause {
+                scopes: Some(vec![PauseScope::Purchases]),
+            },
+        )
+        .unwrap();
+    let err = suite.purchase(buyer.as_str(), 100, None, None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::Paused { .. }
+    ));
+    suite
+        .execute(0, ExecuteMsg::UpdateTimelockDelay { delay: 10 })
+        .unwrap();
+
+    suite
+        .execute(0, ExecuteMsg::Unpause { scopes: None })
+        .unwrap();
+    suite.purchase(buyer.as_str(), 100, None, None).unwrap();
+
+    // Bogus purchases can still be reversed while purchases are paused.
+    suite
+        .execute(
+            0,
+            ExecuteMsg::Pause {
+                scopes: Some(vec![PauseScope::Purchases]),
+            },
+        )
+        .unwrap();
+    suite
+        .execute(
+            0,
+            ExecuteMsg::AdjustPurchase {
+                chain_id: CHAIN_ID.to_string(),
+                round_id: None,
+                purchaser: buyer.to_string(),
+                delta: Int128::new(-100),
+                reason: "relayer key leaked".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(suite.purchased(buyer.as_str()), Uint128::zero());
+    suite
+        .execute(0, ExecuteMsg::Unpause { scopes: None })
+        .unwrap();
+
+    suite
+        .execute(0, ExecuteMsg::Pause { scopes: None })
+        .unwrap();
+    let err = suite.execute(0, ExecuteMsg::CancelSale {});
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::Paused { .. }
+    ));
+    suite
+        .execute(0, ExecuteMsg::Unpause { scopes: None })
+        .unwrap();
+    suite.execute(0, ExecuteMsg::CancelSale {}).unwrap();
+}
+
+#[test]
+fn round_adjustments_allocate_at_the_round_price() {
+    let mut suite = Suite::new(1, None, 0);
+    let alice = suite.app.api().addr_make("alice");
+    let bob = suite.app.api().addr_make("bob");
+    let treasury = suite.app.api().addr_make("treasury");
+    let now = suite.app.block_info().time;
+    suite
+        .execute(
+            0,
+            ExecuteMsg::CreateRound {
+                name: "private".to_string(),
+                price: Decimal::from_ratio(10u128, 1u128),
+                cap: None,
+                start_time: now,
+                end_time: now.plus_seconds(SALE_DURATION),
+                allowlist_only: false,
+            },
+        )
+        .unwrap();
+    suite
+        .execute(0, ExecuteMsg::OpenRound { round_id: 1 })
+        .unwrap();
+    suite
+        .purchase(alice.as_str(), 1_000, Some(1), None)
+        .unwrap();
+
+    // Bob is credited 1000 then 500 is taken back, leaving him half of Alice's allocation.
+    for delta in [1_000, -500] {
+        suite
+            .execute(
+                0,
+                ExecuteMsg::AdjustPurchase {
+                    chain_id: CHAIN_ID.to_string(),
+                    round_id: Some(1),
+                    purchaser: bob.to_string(),
+                    delta: Int128::new(delta),
+                    reason: "missed relay".to_string(),
+                },
+            )
+            .unwrap();
+    }
+    let round_purchase: PurchaseResponse = suite.query(QueryMsg::RoundPurchase {
+        round_id: 1,
+        purchaser: bob.to_string(),
+    });
+    assert_eq!(round_purchase.amount, Uint128::new(500));
+
+    suite.advance(SALE_DURATION);
+    suite
+        .execute(
+            0,
+            ExecuteMsg::Finalize {
+                mint_amount: Uint128::new(1_100),
+                distribute_amount: Uint128::new(1_100),
+                pusd_amount: Uint128::new(1_500),
+                treasury: treasury.to_string(),
+            },
+        )
+        .unwrap();
+    suite
+        .execute(0, ExecuteMsg::ProcessDistribution { limit: None })
+        .unwrap();
+    let denom = format!("factory/{}/gpu", suite.contract);
+    assert_eq!(suite.balance(&alice, &denom), Uint128::new(733));
+    assert_eq!(suite.balance(&bob, &denom), Uint128::new(366));
+}
+
+#[test]
+fn token_metadata_is_validated_and_locked_at_finalize() {
+    let mut suite = Suite::new(1, None, 0);
+    let treasury = suite.app.api().addr_make("treasury");
+    let token: TokenMetadata = suite.query(QueryMsg::TokenMetadata {});
+    let invalid: [fn(&mut TokenMetadata); 5] = [
+        |token| token.subdenom = String::new(),
+        |token| token.subdenom = "g".repeat(45),
+        |token| token.subdenom = "gpu/dao".to_string(),
+        |token| token.symbol = String::new(),
+        |token| token.decimals = 19,
+    ];
+    for update in invalid {
+        let mut token_metadata = token.clone();
+        update(&mut token_metadata);
+        let err = suite.execute(0, ExecuteMsg::UpdateTokenMetadata { token_metadata });
+        assert!(matches!(
+            contract_error(err.unwrap_err()),
+            ContractError::InvalidTokenMetadata { .. }
+        ));
+    }
+
+    let token_metadata = TokenMetadata {
+        subdenom: "gpu-dao".to_string(),
+        decimals: 18,
+        ..token
+    };
+    suite
+        .execute(
+            0,
+            ExecuteMsg::UpdateTokenMetadata {
+                token_metadata: token_metadata.clone(),
+            },
+        )
+        .unwrap();
+    let stored: TokenMetadata = suite.query(QueryMsg::TokenMetadata {});
+    assert_eq!(stored, token_metadata);
+
+    suite.advance(SALE_DURATION);
+    suite
+        .execute(
+            0,
+            ExecuteMsg::Finalize {
+                mint_amount: Uint128::new(1_000),
+                distribute_amount: Uint128::new(100),
+                pusd_amount: Uint128::zero(),
+                treasury: treasury.to_string(),
+            },
+        )
+        .unwrap();
+    let denom = format!("factory/{}/gpu-dao", suite.contract);
+    assert_eq!(suite.balance(&treasury, &denom), Uint128::new(900));
+    let err = suite.execute(0, ExecuteMsg::UpdateTokenMetadata { token_metadata });
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::AlreadyFinalized {}
+    ));
+}
+
+#[test]
+fn bridge_token_binds_the_denom_once_it_exists() {
+    let mut suite = Suite::new(1, None, 0);
+    let treasury = suite.app.api().addr_make("treasury");
+    let gpu_token = "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb";
+    let redeployed_token = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let err = suite.execute(
+        0,
+        ExecuteMsg::BridgeToken {
+            chain_id: CHAIN_ID.to_string(),
+            erc20_address: "not an address".to_string(),
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::InvalidEvmAddress { .. }
+    ));
+
+    // Before finalize the address is only recorded; finalize binds it.
+    let response = suite
+        .execute(
+            0,
+            ExecuteMsg::BridgeToken {
+                chain_id: CHAIN_ID.to_string(),
+                erc20_address: gpu_token.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(paloma_msgs(&response), 0);
+    let erc20_address: Option<String> = suite.query(QueryMsg::Erc20Address {
+        chain_id: CHAIN_ID.to_string(),
+    });
+    assert_eq!(erc20_address.as_deref(), Some(gpu_token));
+
+    suite.advance(SALE_DURATION);
+    let response = suite
+        .execute(
+            0,
+            ExecuteMsg::Finalize {
+                mint_amount: Uint128::new(1_000),
+                distribute_amount: Uint128::new(1_000),
+                pusd_amount: Uint128::zero(),
+                treasury: treasury.to_string(),
+            },
+        )
+        .unwrap();
+    // Denom creation and the Skyway binding.
+    assert_eq!(paloma_msgs(&response), 2);
+
+    // Afterwards the binding is sent right away.
+    let response = suite
+        .execute(
+            0,
+            ExecuteMsg::BridgeToken {
+                chain_id: CHAIN_ID.to_string(),
+                erc20_address: redeployed_token.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(paloma_msgs(&response), 1);
+    let event = response
+        .events
+        .iter()
+        .find(|event| event.ty == "paloma")
+        .unwrap();
+    assert!(event.attributes[0].value.contains("set_erc20_to_denom"));
+    assert!(event.attributes[0].value.contains(redeployed_token));
+}
+
+#[test]
+fn sale_caps_limit_purchases_and_report_remaining_capacity() {
+    let mut suite = Suite::new(1, None, 0);
+    let alice = suite.app.api().addr_make("alice");
+    let bob = suite.app.api().addr_make("bob");
+    let treasury = suite.app.api().addr_make("treasury");
+    let sale_caps = SaleCaps {
+        hard_cap: Some(Uint128::new(800)),
+        soft_cap: Some(Uint128::new(600)),
+        max_per_purchaser: Some(Uint128::new(500)),
+        min_purchase: Some(Uint128::new(10)),
+    };
+    let err = suite.execute(
+        0,
+        ExecuteMsg::UpdateSaleCaps {
+            sale_caps: SaleCaps {
+                soft_cap: Some(Uint128::new(900)),
+                ..sale_caps.clone()
+            },
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::InvalidSaleCaps { .. }
+    ));
+    suite
+        .execute(0, ExecuteMsg::UpdateSaleCaps { sale_caps })
+        .unwrap();
+
+    let err = suite.purchase(alice.as_str(), 5, None, None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::BelowMinimumPurchase { .. }
+    ));
+    suite.purchase(alice.as_str(), 400, None, None).unwrap();
+    let remaining: RemainingCapacityResponse = suite.query(QueryMsg::RemainingCapacity {
+        purchaser: Some(alice.to_string()),
+    });
+    assert_eq!(
+        remaining,
+        RemainingCapacityResponse {
+            total_purchased: Uint128::new(400),
+            soft_cap_reached: false,
+            hard_cap_remaining: Some(Uint128::new(400)),
+            purchaser_remaining: Some(Uint128::new(100)),
+        }
+    );
+    let err = suite.purchase(alice.as_str(), 200, None, None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::ExceedsPurchaserCap { .. }
+    ));
+    let err = suite.execute(
+        0,
+        ExecuteMsg::Finalize {
+            mint_amount: Uint128::new(1_000),
+            distribute_amount: Uint128::new(1_000),
+            pusd_amount: Uint128::zero(),
+            treasury: treasury.to_string(),
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::SoftCapNotReached { .. }
+    ));
+
+    let err = suite.purchase(bob.as_str(), 450, None, None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::ExceedsHardCap { .. }
+    ));
+    suite.purchase(bob.as_str(), 400, None, None).unwrap();
+    let remaining: RemainingCapacityResponse =
+        suite.query(QueryMsg::RemainingCapacity { purchaser: None });
+    assert!(remaining.soft_cap_reached);
+    assert_eq!(remaining.hard_cap_remaining, Some(Uint128::zero()));
+    assert_eq!(remaining.purchaser_remaining, None);
+
+    // A sold-out sale can be finalized before its window closes.
+    suite
+        .execute(
+            0,
+            ExecuteMsg::Finalize {
+                mint_amount: Uint128::new(1_000),
+                distribute_amount: Uint128::new(1_000),
+                pusd_amount: Uint128::zero(),
+                treasury: treasury.to_string(),
+            },
+        )
+        .unwrap();
+}
+
+#[test]
+fn purchases_are_accepted_only_within_the_sale_window() {
+    let mut suite = Suite::with_start_delay(1, None, 0, 100);
+    let buyer = suite.app.api().addr_make("buyer");
+    let err = suite.purchase(buyer.as_str(), 100, None, None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::SaleNotStarted { .. }
+    ));
+
+    suite.advance(100);
+    suite.purchase(buyer.as_str(), 100, None, None).unwrap();
+    let state: State = suite.query(QueryMsg::Config {});
+    let err = suite.execute(
+        0,
+        ExecuteMsg::ExtendSaleWindow {
+            end_time: state.end_time,
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::InvalidSaleWindow { .. }
+    ));
+    let end_time = state.end_time.plus_seconds(500);
+    suite
+        .execute(0, ExecuteMsg::ExtendSaleWindow { end_time })
+        .unwrap();
+
+    // Still open past the original end, closed from the extended one on.
+    suite.advance(SALE_DURATION);
+    suite.purchase(buyer.as_str(), 100, None, None).unwrap();
+    suite.advance(500);
+    let err = suite.purchase(buyer.as_str(), 100, None, None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::SaleEnded { .. }
+    ));
+    let err = suite.execute(
+        0,
+        ExecuteMsg::ExtendSaleWindow {
+            end_time: end_time.plus_seconds(500),
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::SaleEnded { .. }
+    ));
+    assert_eq!(suite.purchased(buyer.as_str()), Uint128::new(200));
+}
+
+#[test]
+fn rounds_enforce_their_window_allowlist_and_cap() {
+    let mut suite = Suite::new(1, None, 0);
+    let alice = suite.app.api().addr_make("alice");
+    let bob = suite.app.api().addr_make("bob");
+    let now = suite.app.block_info().time;
+    let round = |start: u64, end: u64| ExecuteMsg::CreateRound {
+        name: "seed".to_string(),
+        price: Decimal::percent(50),
+        cap: Some(Uint128::new(1_000)),
+        start_time: now.plus_seconds(start),
+        end_time: now.plus_seconds(end),
+        allowlist_only: true,
+    };
+    let err = suite.execute(0, round(0, SALE_DURATION + 1));
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::InvalidRound { .. }
+    ));
+    suite.execute(0, round(100, 200)).unwrap();
+
+    suite
+        .execute(
+            0,
+            ExecuteMsg::UpdateRoundAllowlist {
+                round_id: 1,
+                add: vec![alice.to_string()],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    suite
+        .execute(0, ExecuteMsg::OpenRound { round_id: 1 })
+        .unwrap();
+    let err = suite.purchase(alice.as_str(), 100, Some(1), None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::RoundNotOpen { round_id: 1 }
+    ));
+
+    suite.advance(100);
+    let err = suite.purchase(bob.as_str(), 100, Some(1), None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::NotAllowlisted { round_id: 1, .. }
+    ));
+    suite.purchase(alice.as_str(), 600, Some(1), None).unwrap();
+    let err = suite.purchase(alice.as_str(), 500, Some(1), None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::ExceedsRoundCap { round_id: 1, .. }
+    ));
+
+    // Closing the round or its window ending stops purchases in it.
+    suite
+        .execute(0, ExecuteMsg::CloseRound { round_id: 1 })
+        .unwrap();
+    let err = suite.purchase(alice.as_str(), 100, Some(1), None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::RoundNotOpen { round_id: 1 }
+    ));
+    suite
+        .execute(0, ExecuteMsg::OpenRound { round_id: 1 })
+        .unwrap();
+    suite.advance(100);
+    let err = suite.purchase(alice.as_str(), 100, Some(1), None);
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::RoundNotOpen { round_id: 1 }
+    ));
+    let round_purchase: PurchaseResponse = suite.query(QueryMsg::RoundPurchase {
+        round_id: 1,
+        purchaser: alice.to_string(),
+    });
+    assert_eq!(round_purchase.amount, Uint128::new(600));
+
+    suite.execute(0, ExecuteMsg::CancelSale {}).unwrap();
+    let err = suite.execute(0, round(300, 400));
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::SaleCancelled {}
+    ));
+}
+
+#[test]
+fn relayed_purchases_are_applied_once_per_origin() {
+    let mut suite = Suite::new(1, None, 0);
+    let buyer = suite.app.api().addr_make("buyer");
+    let tx_hash = HexBinary::from(vec![0xab; 32]);
+    let purchase = |log_index: u64| ExecuteMsg::Purchase {
+        chain_id: CHAIN_ID.to_string(),
+        round_id: None,
+        purchaser: buyer.to_string(),
+        amount: Uint128::new(100),
+        allocation_proof: None,
+        origin: PurchaseOrigin {
+            tx_hash: tx_hash.clone(),
+            log_index,
+            payment_token: PAYMENT_TOKEN.to_string(),
+        },
+    };
+    suite.execute(0, purchase(0)).unwrap();
+    let err = suite.execute(0, purchase(0));
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::DuplicatePurchase { log_index: 0, .. }
+    ));
+    // Another log of the same transaction is a separate purchase.
+    suite.execute(0, purchase(1)).unwrap();
+    assert_eq!(suite.purchased(buyer.as_str()), Uint128::new(200));
+
+    let record: Option<PurchaseRecord> = suite.query(QueryMsg::PurchaseByOrigin {
+        chain_id: CHAIN_ID.to_string(),
+        tx_hash: tx_hash.clone(),
+        log_index: 1,
+    });
+    let record = record.unwrap();
+    assert_eq!(record.id, 2);
+    assert_eq!(record.origin.tx_hash, tx_hash);
+    let record: Option<PurchaseRecord> = suite.query(QueryMsg::PurchaseByOrigin {
+        chain_id: CHAIN_ID.to_string(),
+        tx_hash,
+        log_index: 2,
+    });
+    assert!(record.is_none());
+}
+
+#[test]
+fn purchase_logs_are_decoded_from_the_registered_sale_contract() {
+    let mut suite = Suite::new(1, None, 0);
+    let sale_contract = "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb";
+    let buyer = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+    let address = |address: &str| ethabi::Token::Address(address.parse().unwrap());
+    let signature = ethabi::long_signature(
+        "Purchased",
+        &[
+            ethabi::ParamType::Address,
+            ethabi::ParamType::Address,
+            ethabi::ParamType::Uint(256),
+        ],
+    );
+    let log = |emitter: &str, amount: u64, log_index: u64| RelayedLog {
+        address: emitter.to_string(),
+        topics: vec![
+            HexBinary::from(signature.as_bytes()),
+            HexBinary::from(ethabi::encode(&[address(buyer)])),
+            HexBinary::from(ethabi::encode(&[address(PAYMENT_TOKEN)])),
+        ],
+        data: HexBinary::from(ethabi::encode(&[ethabi::Token::Uint(amount.into())])),
+        tx_hash: HexBinary::from(vec![0xcd; 32]),
+        log_index,
+    };
+
+    let err = suite.execute(
+        0,
+        ExecuteMsg::IngestPurchaseLogs {
+            chain_id: CHAIN_ID.to_string(),
+            logs: vec![log(sale_contract, 100, 0)],
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::SaleContractNotSet { .. }
+    ));
+    suite
+        .execute(
+            0,
+            ExecuteMsg::RegisterChain {
+                chain_id: "bsc-main".to_string(),
+                chain_settings: ChainSettings {
+                    sale_contract: Some(sale_contract.to_string()),
+                    ..chain_settings("bsc-job")
+                },
+            },
+        )
+        .unwrap();
+
+    // A log from any other contract rejects the whole batch.
+    let err = suite.execute(
+        0,
+        ExecuteMsg::IngestPurchaseLogs {
+            chain_id: "bsc-main".to_string(),
+            logs: vec![log(sale_contract, 100, 0), log(PAYMENT_TOKEN, 100, 1)],
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::UnexpectedLogEmitter { .. }
+    ));
+    assert_eq!(suite.purchased(buyer), Uint128::zero());
+
+    suite
+        .execute(
+            0,
+            ExecuteMsg::IngestPurchaseLogs {
+                chain_id: "bsc-main".to_string(),
+                logs: vec![log(sale_contract, 100, 0), log(sale_contract, 250, 1)],
+            },
+        )
+        .unwrap();
+    assert_eq!(suite.purchased(buyer), Uint128::new(350));
+    let record: Option<PurchaseRecord> = suite.query(QueryMsg::PurchaseByOrigin {
+        chain_id: "bsc-main".to_string(),
+        tx_hash: HexBinary::from(vec![0xcd; 32]),
+        log_index: 1,
+    });
+    let record = record.unwrap();
+    assert_eq!(record.purchaser.to_string(), buyer);
+    assert_eq!(record.amount, Uint128::new(250));
+    assert_eq!(
+        record.origin.payment_token.to_lowercase(),
+        PAYMENT_TOKEN.to_lowercase()
+    );
+}
+
+#[test]
+fn batch_purchases_apply_all_entries_or_none() {
+    let mut suite = Suite::new(1, None, 0);
+    let alice = suite.app.api().addr_make("alice");
+    let bob = suite.app.api().addr_make("bob");
+    let entry = |purchaser: &Addr, amount: u128, tx: u8| PurchaseEntry {
+        chain_id: CHAIN_ID.to_string(),
+        round_id: None,
+        purchaser: purchaser.to_string(),
+        amount: Uint128::new(amount),
+        allocation_proof: None,
+        origin: PurchaseOrigin {
+            tx_hash: HexBinary::from(vec![tx; 32]),
+            log_index: 0,
+            payment_token: PAYMENT_TOKEN.to_string(),
+        },
+    };
+
+    let err = suite.execute(0, ExecuteMsg::BatchPurchase { entries: vec![] });
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::EmptyBatch {}
+    ));
+    let err = suite.execute_as(
+        &alice,
+        ExecuteMsg::BatchPurchase {
+            entries: vec![entry(&alice, 100, 1)],
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::Unauthorized {}
+    ));
+
+    // The last entry replays the first one's origin, so nothing is recorded.
+    let err = suite.execute(
+        0,
+        ExecuteMsg::BatchPurchase {
+            entries: vec![
+                entry(&alice, 100, 1),
+                entry(&bob, 200, 2),
+                entry(&bob, 300, 1),
+            ],
+        },
+    );
+    assert!(matches!(
+        contract_error(err.unwrap_err()),
+        ContractError::DuplicatePurchase { .. }
+    ));
+    let total: TotalPurchasedResponse = suite.query(QueryMsg::TotalPurchased {});
+    assert_eq!(total.total, Uint128::zero());
+
+    let response = suite
+        .execute(
+            0,
+            ExecuteMsg::BatchPurchase {
+                entries: vec![
+                    entry(&alice, 100, 1),
+                    entry(&bob, 200, 2),
+                    entry(&bob, 300, 3),
+                ],
+            },
+        )
+        .unwrap();
+    let entries: Vec<&str> = response
+        .events
+        .iter()
+        .filter(|event| event.ty == "wasm-purchase")
+        .flat_map(|event| &event.attributes)
+        .filter(|attribute| attribute.key == "entry")
+        .map(|attribute| attribute.value.as_str())
+        .collect();
+    assert_eq!(entries, ["0", "1", "2"]);
+    assert_eq!(suite.purchased(alice.as_str()), Uint128::new(100));
+    assert_eq!(suite.purchased(bob.as_str()), Uint128::new(500));
+    let total: TotalPurchasedResponse = suite.query(QueryMsg::TotalPurchased {});
+    assert_eq!(total.total, Uint128::new(600));
+}
+
+#[test]
+fn purchase_history_pages_by_purchaser_and_chain() {
+    let mut suite = Suite::new(1, None, 0);
+    let alice = suite.app.api().addr_make("alice");
+    let bob = suite.app.api().addr_make("bob");
+    suite
+        .execute(
+            0,
+            ExecuteMsg::RegisterChain {
+                chain_id: "bsc-main".to_string(),
+                chain_settings: chain_settings("bsc-job"),
+            },
+        )
+        .unwrap();
+    // Records 1, 2 and 4 are Alice's on eth-main; 3 is Bob's, 5 is Alice's on bsc-main.
+    for (purchaser, amount) in [(&alice, 100), (&alice, 200), (&bob, 300), (&alice, 400)] {
+        suite
+            .purchase(purchaser.as_str(), amount, None, None)
+            .unwrap();
+    }
+    suite
+        .execute(
+            0,
+            ExecuteMsg::Purchase {
+                chain_id: "bsc-main".to_string(),
+                round_id: None,
+                purchaser: alice.to_string(),
+                amount: Uint128::new(500),
+                allocation_proof: None,
+                origin: PurchaseOrigin {
+                    tx_hash: HexBinary::from(vec![0xef; 32]),
+                    log_index: 0,
+                    payment_token: PAYMENT_TOKEN.to_string(),
+                },
+            },
+        )
+        .unwrap();
+
+    let ids = |response: PurchaseRecordsResponse| -> Vec<u64> {
+        response.records.iter().map(|record| record.id).collect()
+    };
+    let by_purchaser = |start_after: Option<u64>| QueryMsg::PurchasesByPurchaser {
+        purchaser: alice.as_str().to_uppercase(),
+        start_after,
+        limit: Some(2),
+    };
+    assert_eq!(ids(suite.query(by_purchaser(None))), [1, 2]);
+    assert_eq!(ids(suite.query(by_purchaser(Some(2)))), [4, 5]);
+    assert_eq!(ids(suite.query(by_purchaser(Some(5)))), Vec::<u64>::new());
+
+    let by_chain = |chain_id: &str, start_after: Option<u64>| QueryMsg::PurchasesByChain {
+        chain_id: chain_id.to_string(),
+        start_after,
+        limit: Some(3),
+    };
+    assert_eq!(ids(suite.query(by_chain(CHAIN_ID, None))), [1, 2, 3]);
+    assert_eq!(ids(suite.query(by_chain(CHAIN_ID, Some(3)))), [4]);
+    assert_eq!(ids(suite.query(by_chain("bsc-main", None))), [5]);
+}
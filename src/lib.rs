@@ -1,5 +1,7 @@
 pub mod contract;
 mod error;
+#[cfg(test)]
+mod integration_tests;
 pub mod msg;
 pub mod state;
 
@@ -1,26 +1,175 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{CustomMsg, Uint128, Uint256};
+use cosmwasm_std::{Binary, CustomMsg, Decimal, HexBinary, Int128, Timestamp, Uint128, Uint256};
+
+use crate::state::{
+    ChainSettings, Distribution, PauseScope, PauseStatus, PendingOwner, Proposal,
+    PurchaseAdjustment, PurchaseRecord, QueuedChange, Round, SaleCaps, State, TokenMetadata,
+};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owners: Vec<String>,
+    /// Owner approvals required for governance messages; defaults to 1.
+    pub threshold: Option<u32>,
+    /// Seconds chain setting changes stay queued before they can be executed.
+    pub timelock_delay: u64,
+    pub token_metadata: TokenMetadata,
+    /// Purchase limits; no limits when omitted.
+    pub sale_caps: Option<SaleCaps>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Settings missing from contracts still on the original `{owners, finished}`
+    /// state; required only when migrating those.
+    pub legacy: Option<LegacyUpgrade>,
+}
+
+#[cw_serde]
+pub struct LegacyUpgrade {
+    /// Source chain of every purchase recorded before per-chain tracking.
+    pub chain_id: String,
+    /// Settings registered for `chain_id`, which legacy contracts never stored.
+    pub chain_settings: ChainSettings,
+    pub threshold: Option<u32>,
+    pub timelock_delay: u64,
+    pub token_metadata: TokenMetadata,
+    pub sale_caps: Option<SaleCaps>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    /// Records a purchase, in `round_id` if given, otherwise at the flat sale terms.
     Purchase {
+        chain_id: String,
+        round_id: Option<u64>,
+        /// Bech32 or `0x` address; stored in canonical (EIP-55 for EVM) form.
         purchaser: String,
+        /// Amount of `origin.payment_token` paid.
         amount: Uint128,
+        /// Required in rounds with a Merkle root.
+        allocation_proof: Option<AllocationProof>,
+        origin: PurchaseOrigin,
     },
+    /// Records several purchases atomically; each entry is checked like `Purchase`.
+    BatchPurchase {
+        entries: Vec<PurchaseEntry>,
+    },
+    /// Credits purchases from `Purchased` logs relayed from the chain's sale contract.
+    IngestPurchaseLogs {
+        chain_id: String,
+        logs: Vec<RelayedLog>,
+    },
+    /// Corrects a purchaser's amount on `chain_id` before finalize, e.g. after a reorg.
+    /// Increases are held to the sale caps; with `round_id` the round totals and
+    /// cap are adjusted too.
+    AdjustPurchase {
+        chain_id: String,
+        round_id: Option<u64>,
+        purchaser: String,
+        delta: Int128,
+        reason: String,
+    },
+    /// Mints `distribute_amount` to the contract for `ProcessDistribution` and the
+    /// rest of `mint_amount` to `treasury`.
     Finalize {
         mint_amount: Uint128,
         distribute_amount: Uint128,
+        /// pUSD raised by the sale; recorded for reporting only.
         pusd_amount: Uint128,
+        /// Receives the undistributed supply and the distribution rounding dust.
+        treasury: String,
+    },
+    AddOwner {
+        owner: String,
+    },
+    /// Removes an owner; the last owner cannot be removed.
+    RemoveOwner {
+        owner: String,
+    },
+    /// Hands the seat of the `replacing` owner over to `new_owner` once accepted;
+    /// without `replacing`, `new_owner` joins the owners.
+    ProposeOwner {
+        new_owner: String,
+        replacing: Option<String>,
+    },
+    AcceptOwnership {},
+    UpdateThreshold {
+        threshold: u32,
+    },
+    /// Opens a proposal to run a governance message once `threshold` owners approve.
+    /// The proposer's approval is counted.
+    Propose {
+        msg: Box<ExecuteMsg>,
+        /// Seconds until the proposal expires; defaults to 7 days.
+        expires_in: Option<u64>,
+    },
+    Vote {
+        proposal_id: u64,
+    },
+    ExecuteProposal {
+        proposal_id: u64,
+    },
+    /// Cancels an open proposal; only its proposer may do so.
+    CancelProposal {
+        proposal_id: u64,
+    },
+    /// Pushes back `end_time`; only allowed while the sale is still open.
+    ExtendSaleWindow {
+        end_time: Timestamp,
+    },
+    CreateRound {
+        name: String,
+        price: Decimal,
+        cap: Option<Uint128>,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        allowlist_only: bool,
+    },
+    OpenRound {
+        round_id: u64,
+    },
+    CloseRound {
+        round_id: u64,
+    },
+    UpdateRoundMerkleRoot {
+        round_id: u64,
+        merkle_root: Option<HexBinary>,
+    },
+    UpdateRoundAllowlist {
+        round_id: u64,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    UpdateSaleCaps {
+        sale_caps: SaleCaps,
+    },
+    UpdateTokenMetadata {
+        token_metadata: TokenMetadata,
+    },
+    /// Pays out the next `limit` purchasers after `Finalize`.
+    ProcessDistribution {
+        limit: Option<u32>,
+    },
+    /// Binds the sale denom to `erc20_address` on `chain_id` through Skyway.
+    BridgeToken {
+        chain_id: String,
+        erc20_address: String,
+    },
+    CancelSale {},
+    /// Refunds the sender, or the given purchasers when called by an owner; owner
+    /// batches skip purchasers already refunded or with nothing to refund.
+    Refund {
+        purchasers: Option<Vec<String>>,
     },
-    Refund {},
     SetPaloma {
         chain_id: String,
     },
+    /// Queued behind the timelock, like the other chain setting updates below.
     UpdateCompass {
         chain_id: String,
         new_compass: String,
@@ -41,20 +190,227 @@ pub enum ExecuteMsg {
         chain_id: String,
         new_service_fee: Uint256,
     },
+    /// Applies a queued change once its eta has passed.
+    ExecuteQueuedChange {
+        change_id: u64,
+    },
+    CancelQueuedChange {
+        change_id: u64,
+    },
+    /// Queued behind the current delay, so a shorter delay cannot be used right away.
+    UpdateTimelockDelay {
+        delay: u64,
+    },
+    RegisterChain {
+        chain_id: String,
+        chain_settings: ChainSettings,
+    },
+    /// Replaces the chain settings once queued behind the timelock.
+    UpdateChain {
+        chain_id: String,
+        chain_settings: ChainSettings,
+    },
+    RemoveChain {
+        chain_id: String,
+    },
+    /// Queued behind the timelock; repoints `main_job_id` when executed.
+    CreateJob {
+        chain_id: String,
+        job_id: String,
+        definition: JobDefinition,
+        payload: Binary,
+        payload_modifiable: bool,
+        is_mev: bool,
+        permissions: JobPermissions,
+    },
+    /// Queued behind the timelock.
+    UpdateJob {
+        chain_id: String,
+        definition: Option<JobDefinition>,
+        payload: Option<Binary>,
+        permissions: Option<JobPermissions>,
+    },
+    /// Pauses the given scopes, or every pausable message when omitted.
+    Pause {
+        scopes: Option<Vec<PauseScope>>,
+    },
+    /// Lifts the given scopes, or the whole pause when omitted.
+    Unpause {
+        scopes: Option<Vec<PauseScope>>,
+    },
+}
+
+/// Proof that `(purchaser, max_allocation)` is a leaf of the round's Merkle tree.
+///
+/// Leaves are `sha256(purchaser ++ max_allocation)` over the decimal string of
+/// `max_allocation`; sibling pairs are sorted before hashing.
+#[cw_serde]
+pub struct AllocationProof {
+    pub max_allocation: Uint128,
+    pub proof: Vec<HexBinary>,
+}
+
+/// Single purchase of a `BatchPurchase`, with the same fields as `Purchase`.
+#[cw_serde]
+pub struct PurchaseEntry {
+    pub chain_id: String,
+    pub round_id: Option<u64>,
+    pub purchaser: String,
+    pub amount: Uint128,
+    pub allocation_proof: Option<AllocationProof>,
+    pub origin: PurchaseOrigin,
+}
+
+/// EVM event a purchase was relayed from.
+#[cw_serde]
+pub struct PurchaseOrigin {
+    /// Hash of the transaction that emitted the purchase event.
+    pub tx_hash: HexBinary,
+    pub log_index: u64,
+    /// ERC20 the purchaser paid with.
+    pub payment_token: String,
+}
+
+/// EVM log relayed by Paloma.
+///
+/// Expected to be `Purchased(address indexed purchaser, address indexed token, uint256 amount)`,
+/// credited at the flat sale terms.
+#[cw_serde]
+pub struct RelayedLog {
+    /// Contract that emitted the log.
+    pub address: String,
+    pub topics: Vec<HexBinary>,
+    pub data: HexBinary,
+    pub tx_hash: HexBinary,
+    pub log_index: u64,
+}
+
+impl ExecuteMsg {
+    /// Whether the message needs an approved proposal when the threshold is above one.
+    pub fn requires_proposal(&self) -> bool {
+        !matches!(
+            self,
+            ExecuteMsg::Purchase { .. }
+                | ExecuteMsg::BatchPurchase { .. }
+                | ExecuteMsg::IngestPurchaseLogs { .. }
+                | ExecuteMsg::AcceptOwnership {}
+                | ExecuteMsg::ProcessDistribution { .. }
+                | ExecuteMsg::Refund { .. }
+                | ExecuteMsg::Propose { .. }
+                | ExecuteMsg::Vote { .. }
+                | ExecuteMsg::ExecuteProposal { .. }
+                | ExecuteMsg::CancelProposal { .. }
+                | ExecuteMsg::ExecuteQueuedChange { .. }
+                | ExecuteMsg::CancelQueuedChange { .. }
+                | ExecuteMsg::Pause { .. }
+        )
+    }
+
+    /// Whether the message is stopped by a global pause.
+    pub fn is_pausable(&self) -> bool {
+        !matches!(
+            self,
+            ExecuteMsg::AddOwner { .. }
+                | ExecuteMsg::RemoveOwner { .. }
+                | ExecuteMsg::ProposeOwner { .. }
+                | ExecuteMsg::AcceptOwnership {}
+                | ExecuteMsg::UpdateThreshold { .. }
+                | ExecuteMsg::Propose { .. }
+                | ExecuteMsg::Vote { .. }
+                | ExecuteMsg::ExecuteProposal { .. }
+                | ExecuteMsg::CancelProposal { .. }
+                | ExecuteMsg::CancelQueuedChange { .. }
+                | ExecuteMsg::Pause { .. }
+                | ExecuteMsg::Unpause { .. }
+        )
+    }
+
+    /// Pause scope the message belongs to, if any. `AdjustPurchase` has none, so
+    /// bogus purchases can be reversed while purchases are paused.
+    pub fn pause_scope(&self) -> Option<PauseScope> {
+        match self {
+            ExecuteMsg::Purchase { .. }
+            | ExecuteMsg::BatchPurchase { .. }
+            | ExecuteMsg::IngestPurchaseLogs { .. } => Some(PauseScope::Purchases),
+            ExecuteMsg::BridgeToken { .. }
+            | ExecuteMsg::SetPaloma { .. }
+            | ExecuteMsg::UpdateCompass { .. }
+            | ExecuteMsg::UpdateRefundWallet { .. }
+            | ExecuteMsg::UpdateGasFee { .. }
+            | ExecuteMsg::UpdateServiceFeeCollector { .. }
+            | ExecuteMsg::UpdateServiceFee { .. }
+            | ExecuteMsg::ExecuteQueuedChange { .. }
+            | ExecuteMsg::RegisterChain { .. }
+            | ExecuteMsg::UpdateChain { .. }
+            | ExecuteMsg::RemoveChain { .. }
+            | ExecuteMsg::CreateJob { .. }
+            | ExecuteMsg::UpdateJob { .. } => Some(PauseScope::CrossChainAdmin),
+            ExecuteMsg::Refund { .. } => Some(PauseScope::Refunds),
+            _ => None,
+        }
+    }
 }
 
 #[cw_serde]
 pub enum PalomaMsg {
+    /// Message struct for cross-chain calls.
+    SchedulerMsg {
+        create_job: Option<CreateJob>,
+        execute_job: Option<ExecuteJob>,
+        update_job: Option<UpdateJob>,
+    },
     /// Message struct for tokenfactory calls.
     TokenFactoryMsg {
         create_denom: Option<CreateDenomMsg>,
         mint_tokens: Option<MintMsg>,
     },
+    /// Message struct for skyway bridge calls.
     SkywayMsg {
-        set_erc20_to_denom: SetErc20ToDenom,
+        send_tx: Option<SendTx>,
+        set_erc20_to_denom: Option<SetErc20ToDenom>,
     },
 }
 
+#[cw_serde]
+pub struct ExecuteJob {
+    pub job_id: String,
+    pub payload: Binary,
+}
+
+#[cw_serde]
+pub struct CreateJob {
+    pub job_id: String,
+    pub chain_type: String,
+    pub chain_reference_id: String,
+    pub definition: JobDefinition,
+    pub payload: Binary,
+    pub payload_modifiable: bool,
+    pub is_mev: bool,
+    pub permissions: JobPermissions,
+}
+
+#[cw_serde]
+pub struct UpdateJob {
+    pub job_id: String,
+    pub definition: Option<JobDefinition>,
+    pub payload: Option<Binary>,
+    pub permissions: Option<JobPermissions>,
+}
+
+/// Target contract of a job and the JSON ABI used to call it.
+#[cw_serde]
+pub struct JobDefinition {
+    pub abi: String,
+    pub address: String,
+}
+
+/// Paloma addresses allowed or denied to trigger a job.
+#[cw_serde]
+pub struct JobPermissions {
+    pub whitelist: Vec<String>,
+    pub blacklist: Vec<String>,
+}
+
 #[cw_serde]
 pub struct CreateDenomMsg {
     pub subdenom: String,
@@ -85,6 +441,14 @@ pub struct MintMsg {
     pub mint_to_address: String,
 }
 
+#[cw_serde]
+pub struct SendTx {
+    pub remote_chain_destination_address: String,
+    /// Coin string, e.g. "100factory/paloma1.../gpu".
+    pub amount: String,
+    pub chain_reference_id: String,
+}
+
 #[cw_serde]
 pub struct SetErc20ToDenom {
     pub erc20_address: String,
@@ -96,4 +460,135 @@ impl CustomMsg for PalomaMsg {}
 
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    #[returns(State)]
+    Config {},
+    #[returns(PauseStatus)]
+    PauseStatus {},
+    #[returns(Option<PendingOwner>)]
+    PendingOwner {},
+    #[returns(Proposal)]
+    Proposal { proposal_id: u64 },
+    #[returns(ProposalsResponse)]
+    OpenProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(QueuedChangesResponse)]
+    QueuedChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(Round)]
+    Round { round_id: u64 },
+    #[returns(RoundsResponse)]
+    Rounds {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(PurchaseResponse)]
+    RoundPurchase { round_id: u64, purchaser: String },
+    #[returns(SaleCaps)]
+    SaleCaps {},
+    /// Remaining room under the hard cap and, if given, the purchaser's cap.
+    #[returns(RemainingCapacityResponse)]
+    RemainingCapacity { purchaser: Option<String> },
+    #[returns(PurchaseResponse)]
+    Purchase { purchaser: String },
+    #[returns(AllPurchasesResponse)]
+    AllPurchases {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(TotalPurchasedResponse)]
+    TotalPurchased {},
+    #[returns(PurchaseRecord)]
+    PurchaseRecord { record_id: u64 },
+    /// Individual purchases of a purchaser, oldest first.
+    #[returns(PurchaseRecordsResponse)]
+    PurchasesByPurchaser {
+        purchaser: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Individual purchases relayed from a chain, oldest first.
+    #[returns(PurchaseRecordsResponse)]
+    PurchasesByChain {
+        chain_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Record created from the given source event, if it was relayed.
+    #[returns(Option<PurchaseRecord>)]
+    PurchaseByOrigin {
+        chain_id: String,
+        tx_hash: HexBinary,
+        log_index: u64,
+    },
+    /// Audit log of owner adjustments to a purchaser's amount.
+    #[returns(PurchaseAdjustmentsResponse)]
+    PurchaseAdjustments {
+        purchaser: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(TokenMetadata)]
+    TokenMetadata {},
+    #[returns(Distribution)]
+    DistributionStatus {},
+    #[returns(Option<String>)]
+    Erc20Address { chain_id: String },
+    #[returns(ChainSettings)]
+    ChainSettings { chain_id: String },
+}
+
+#[cw_serde]
+pub struct ProposalsResponse {
+    pub proposals: Vec<Proposal>,
+}
+
+#[cw_serde]
+pub struct QueuedChangesResponse {
+    pub changes: Vec<QueuedChange>,
+}
+
+#[cw_serde]
+pub struct RoundsResponse {
+    pub rounds: Vec<Round>,
+}
+
+#[cw_serde]
+pub struct RemainingCapacityResponse {
+    pub total_purchased: Uint128,
+    pub soft_cap_reached: bool,
+    /// `None` when there is no hard cap.
+    pub hard_cap_remaining: Option<Uint128>,
+    /// `None` when no purchaser was given or there is no per-purchaser cap.
+    pub purchaser_remaining: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct PurchaseResponse {
+    pub purchaser: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct AllPurchasesResponse {
+    pub purchases: Vec<PurchaseResponse>,
+}
+
+#[cw_serde]
+pub struct PurchaseRecordsResponse {
+    pub records: Vec<PurchaseRecord>,
+}
+
+#[cw_serde]
+pub struct PurchaseAdjustmentsResponse {
+    pub adjustments: Vec<PurchaseAdjustment>,
+}
+
+#[cw_serde]
+pub struct TotalPurchasedResponse {
+    pub total: Uint128,
+}
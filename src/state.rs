@@ -1,14 +1,400 @@
+use std::fmt;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{
+    Addr, Binary, Decimal, HexBinary, Int128, StdError, StdResult, Timestamp, Uint128, Uint256,
+};
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, Key, KeyDeserialize, Map, MultiIndex, Prefixer, PrimaryKey,
+    UniqueIndex,
+};
+
+use crate::msg::{ExecuteMsg, JobDefinition, JobPermissions, PurchaseOrigin};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct State {
     pub owners: Vec<Addr>,
     pub finished: bool,
+    /// Set by owners when the sale failed; enables refunds.
+    pub cancelled: bool,
+    /// Owner approvals required to execute a governance message.
+    pub threshold: u32,
+    /// Seconds a queued change waits before it can be executed.
+    pub timelock_delay: u64,
+    /// Purchases are accepted from `start_time` until `end_time`.
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+}
+
+/// `State` as stored before governance, timelocks and the sale window were added.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LegacyState {
+    pub owners: Vec<Addr>,
+    pub finished: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ChainSettings {
+    /// Paloma job driving the sale contract on this chain.
+    pub main_job_id: String,
+    pub compass: String,
+    /// Wallet EVM refunds are paid from.
+    pub refund_wallet: String,
+    pub gas_fee: Uint256,
+    pub service_fee_collector: String,
+    pub service_fee: Uint256,
+    /// Paloma chain type, e.g. "evm".
+    pub chain_type: String,
+    /// Sale contract whose `Purchased` logs are accepted from this chain.
+    #[serde(default)]
+    pub sale_contract: Option<String>,
+    /// Paloma denom of the bridged payment token; bech32 purchasers are refunded
+    /// in it from the contract balance.
+    #[serde(default)]
+    pub refund_denom: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelockedChange {
+    Compass {
+        new_compass: String,
+    },
+    RefundWallet {
+        new_refund_wallet: String,
+    },
+    GasFee {
+        new_gas_fee: Uint256,
+    },
+    ServiceFeeCollector {
+        new_service_fee_collector: String,
+    },
+    ServiceFee {
+        new_service_fee: Uint256,
+    },
+    ChainSettings {
+        chain_settings: ChainSettings,
+    },
+    CreateJob {
+        job_id: String,
+        definition: JobDefinition,
+        payload: Binary,
+        payload_modifiable: bool,
+        is_mev: bool,
+        permissions: JobPermissions,
+    },
+    UpdateJob {
+        definition: Option<JobDefinition>,
+        payload: Option<Binary>,
+        permissions: Option<JobPermissions>,
+    },
+    TimelockDelay {
+        new_delay: u64,
+    },
+}
+
+/// Cross-chain or timelock setting change waiting out the timelock.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QueuedChange {
+    pub id: u64,
+    /// Chain the change applies to; `None` for `TimelockDelay`.
+    pub chain_id: Option<String>,
+    pub change: TimelockedChange,
+    /// Earliest block time at which the change can be executed.
+    pub eta: Timestamp,
+    pub queued_by: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Open,
+    Executed,
+    Cancelled,
+}
+
+/// Governance message awaiting owner approvals.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Addr,
+    pub msg: ExecuteMsg,
+    pub approvals: Vec<Addr>,
+    pub expires_at: Timestamp,
+    pub status: ProposalStatus,
+}
+
+/// Ownership hand-over proposed by `proposer`, completed once `new_owner` accepts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingOwner {
+    pub proposer: Addr,
+    pub new_owner: Addr,
+    /// Owner whose seat `new_owner` takes; `None` adds `new_owner` alongside.
+    #[serde(default)]
+    pub replacing: Option<Addr>,
+}
+
+/// Purchase limits; `None` disables the corresponding check.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct SaleCaps {
+    /// Maximum total amount the sale accepts.
+    pub hard_cap: Option<Uint128>,
+    /// Minimum total amount required to finalize.
+    pub soft_cap: Option<Uint128>,
+    /// Maximum cumulative amount per purchaser.
+    pub max_per_purchaser: Option<Uint128>,
+    /// Minimum amount of a single purchase.
+    pub min_purchase: Option<Uint128>,
+}
+
+/// Sale round (e.g. seed, private, public) with its own terms.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Round {
+    pub id: u64,
+    pub name: String,
+    /// Payment units per token; purchases in this round are allocated `amount / price`.
+    pub price: Decimal,
+    /// Maximum total amount the round accepts.
+    pub cap: Option<Uint128>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    /// Only purchasers in `ROUND_ALLOWLIST` may buy in this round.
+    pub allowlist_only: bool,
+    /// Root of a Merkle tree of `(purchaser, max_allocation)` leaves. When set,
+    /// purchases must carry a proof and replace the `ROUND_ALLOWLIST` check.
+    pub merkle_root: Option<HexBinary>,
+    /// Toggled by owners; purchases need the round open and within its window.
+    pub open: bool,
+    pub total_purchased: Uint128,
+}
+
+/// Normalized purchaser identity, so the same buyer always maps to one key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Purchaser {
+    /// Bech32 account validated against the chain prefix.
+    Cosmos(Addr),
+    /// EIP-55 checksummed `0x` address.
+    Evm(String),
+}
+
+impl Purchaser {
+    /// Canonical string form, also used as the storage key.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Purchaser::Cosmos(addr) => addr.as_str(),
+            Purchaser::Evm(address) => address,
+        }
+    }
+}
+
+impl fmt::Display for Purchaser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'a> PrimaryKey<'a> for Purchaser {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key<'_>> {
+        vec![Key::Ref(self.as_str().as_bytes())]
+    }
+}
+
+impl<'a> Prefixer<'a> for Purchaser {
+    fn prefix(&self) -> Vec<Key<'_>> {
+        vec![Key::Ref(self.as_str().as_bytes())]
+    }
+}
+
+impl KeyDeserialize for Purchaser {
+    type Output = Purchaser;
+
+    const KEY_ELEMS: u16 = 1;
+
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        let key = String::from_utf8(value).map_err(StdError::invalid_utf8)?;
+        // Keys are only written in canonical form, so the prefix is enough.
+        Ok(if key.starts_with("0x") {
+            Purchaser::Evm(key)
+        } else {
+            Purchaser::Cosmos(Addr::unchecked(key))
+        })
+    }
+}
+
+/// Individual purchase with its EVM provenance, for reconciliation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PurchaseRecord {
+    pub id: u64,
+    pub purchaser: Purchaser,
+    pub chain_id: String,
+    pub round_id: Option<u64>,
+    pub origin: PurchaseOrigin,
+    pub amount: Uint128,
+    pub allocation: Uint128,
+    pub recorded_at: Timestamp,
+}
+
+pub struct PurchaseRecordIndexes<'a> {
+    /// Source event, unique so a relayed event is recorded once.
+    pub origin: UniqueIndex<'a, (String, Vec<u8>, u64), PurchaseRecord, u64>,
+    pub purchaser: MultiIndex<'a, Purchaser, PurchaseRecord, u64>,
+    pub chain: MultiIndex<'a, String, PurchaseRecord, u64>,
+}
+
+impl IndexList<PurchaseRecord> for PurchaseRecordIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<PurchaseRecord>> + '_> {
+        let v: Vec<&dyn Index<PurchaseRecord>> = vec![&self.origin, &self.purchaser, &self.chain];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Audit entry of an owner correction to a purchaser's amount.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PurchaseAdjustment {
+    pub id: u64,
+    pub purchaser: Purchaser,
+    pub chain_id: String,
+    #[serde(default)]
+    pub round_id: Option<u64>,
+    pub delta: Int128,
+    pub reason: String,
+    pub adjusted_by: Addr,
+    /// Purchaser's `PURCHASE_LIST` amount before and after the adjustment.
+    pub amount_before: Uint128,
+    pub amount_after: Uint128,
+    pub adjusted_at: Timestamp,
+}
+
+/// Message groups that can be paused on their own.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseScope {
+    /// Purchase recording and log ingestion.
+    Purchases,
+    /// Chain settings, jobs and bridging.
+    CrossChainAdmin,
+    Refunds,
+}
+
+impl PauseScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PauseScope::Purchases => "purchases",
+            PauseScope::CrossChainAdmin => "cross_chain_admin",
+            PauseScope::Refunds => "refunds",
+        }
+    }
+}
+
+/// Circuit breaker; ownership, governance and the switch itself stay available.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct PauseStatus {
+    /// Every pausable message is stopped.
+    pub all: bool,
+    pub scopes: Vec<PauseScope>,
+}
+
+/// Metadata of the TokenFactory denom created at finalize.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TokenMetadata {
+    pub subdenom: String,
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    /// Display denom unit, e.g. "GPU".
+    pub display: String,
+    /// Exponent of the display unit relative to the base denom.
+    pub decimals: u32,
+}
+
+/// Progress of the post-finalize token distribution.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Distribution {
+    pub denom: String,
+    /// `TOTAL_PURCHASED` snapshot taken at finalize.
+    pub total_purchased: Uint128,
+    /// `TOTAL_ALLOCATION` snapshot taken at finalize; shares are split by allocation.
+    pub total_allocation: Uint128,
+    pub distribute_amount: Uint128,
+    /// pUSD raised by the sale, as reported at finalize; not used in any payout.
+    pub pusd_amount: Uint128,
+    /// Receives the undistributed supply and the rounding dust.
+    pub treasury: Addr,
+    pub distributed: Uint128,
+    /// Number of `PURCHASE_LIST` entries walked so far.
+    pub processed: u64,
+    /// Last purchaser processed.
+    pub cursor: Option<Purchaser>,
+    pub completed: bool,
 }
 
 pub const STATE: Item<State> = Item::new("state");
-pub const PURCHASE_LIST: Map<String, Uint128> = Map::new("purchase_list");
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
+pub const QUEUED_CHANGE_COUNT: Item<u64> = Item::new("queued_change_count");
+pub const QUEUED_CHANGES: Map<u64, QueuedChange> = Map::new("queued_changes");
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+pub const SALE_CAPS: Item<SaleCaps> = Item::new("sale_caps");
+pub const TOKEN_METADATA: Item<TokenMetadata> = Item::new("token_metadata");
+pub const PURCHASE_LIST: Map<Purchaser, Uint128> = Map::new("purchase_list");
+/// Sum of all outstanding `PURCHASE_LIST` amounts.
+pub const TOTAL_PURCHASED: Item<Uint128> = Item::new("total_purchased");
+/// Token allocation of each purchaser, i.e. purchases weighted by their round price.
+pub const ALLOCATIONS: Map<Purchaser, Uint128> = Map::new("allocations");
+pub const TOTAL_ALLOCATION: Item<Uint128> = Item::new("total_allocation");
+pub const ROUND_COUNT: Item<u64> = Item::new("round_count");
+pub const ROUNDS: Map<u64, Round> = Map::new("rounds");
+/// Amount purchased in each round, keyed by (round_id, purchaser).
+pub const ROUND_PURCHASES: Map<(u64, Purchaser), Uint128> = Map::new("round_purchases");
+pub const ROUND_ALLOWLIST: Map<(u64, Purchaser), bool> = Map::new("round_allowlist");
+/// Amount purchased by each purchaser on each source chain, keyed by (purchaser, chain_id).
+pub const PURCHASE_CHAINS: Map<(Purchaser, String), Uint128> = Map::new("purchase_chains");
+/// Outstanding amount purchased on each source chain; a chain cannot be removed
+/// while it is non-zero.
+pub const CHAIN_PURCHASED: Map<String, Uint128> = Map::new("chain_purchased");
+/// Amount refunded to each purchaser once the sale has been cancelled.
+pub const REFUNDED: Map<Purchaser, Uint128> = Map::new("refunded");
+pub const PURCHASE_RECORD_COUNT: Item<u64> = Item::new("purchase_record_count");
+pub const PURCHASE_RECORDS: IndexedMap<u64, PurchaseRecord, PurchaseRecordIndexes<'static>> =
+    IndexedMap::new(
+        "purchase_records",
+        PurchaseRecordIndexes {
+            origin: UniqueIndex::new(
+                |record| {
+                    (
+                        record.chain_id.clone(),
+                        record.origin.tx_hash.to_vec(),
+                        record.origin.log_index,
+                    )
+                },
+                "purchase_records__origin",
+            ),
+            purchaser: MultiIndex::new(
+                |_, record| record.purchaser.clone(),
+                "purchase_records",
+                "purchase_records__purchaser",
+            ),
+            chain: MultiIndex::new(
+                |_, record| record.chain_id.clone(),
+                "purchase_records",
+                "purchase_records__chain",
+            ),
+        },
+    );
+pub const ADJUSTMENT_COUNT: Item<u64> = Item::new("adjustment_count");
+/// Purchase adjustments keyed by (purchaser, adjustment_id).
+pub const ADJUSTMENTS: Map<(Purchaser, u64), PurchaseAdjustment> = Map::new("adjustments");
+pub const DISTRIBUTION: Item<Distribution> = Item::new("distribution");
+/// ERC20 bound to the sale denom on each chain through Skyway.
+pub const ERC20_ADDRESSES: Map<String, String> = Map::new("erc20_addresses");
+pub const CHAIN_SETTINGS: Map<String, ChainSettings> = Map::new("chain_settings");